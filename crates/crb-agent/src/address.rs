@@ -1,49 +1,387 @@
 use crate::agent::Agent;
+use crate::context::AgentContext;
+use crate::dead_letter::{DeadLetter, DeadLetterSink, Undelivered, UndeliveredReason};
+use crate::headers::{self, Headers};
+use crate::mailbox::{self, MailboxCounter, MailboxReceiver, MailboxSender, WeakMailboxSender};
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use crb_core::{mpsc, watch};
+use crb_core::{
+    mpsc::{self, error::TrySendError},
+    uuid::Uuid,
+    watch,
+};
+use crb_runtime::Interruptor;
+use crb_send::Recipient;
 use crb_send::{MessageSender, Sender};
+use futures::future::{poll_fn, select};
+use std::any::{type_name, Any};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::task::Poll;
 
-pub struct AddressJoint<A: Agent + ?Sized> {
-    msg_rx: mpsc::UnboundedReceiver<Envelope<A>>,
+/// The state shared by addresses and the joint of an agent.
+struct Shared {
+    id: Uuid,
+    counter: MailboxCounter,
+    dead_letters: DeadLetterSink,
+    interruptor: OnceLock<Interruptor>,
+    ready: watch::Sender<bool>,
+    /// Strong addresses held outside of the agent's session
+    strong: AtomicUsize,
+    /// The runtime has given a strong address to an owner
+    held: AtomicBool,
+    /// Callbacks of the finish that are taken when it's reported
+    on_finished: Mutex<Option<Vec<FinishedCallback>>>,
+}
+
+type FinishedCallback = Box<dyn FnOnce() + Send>;
+
+impl Shared {
+    fn dead_letter<A: Agent>(&self, envelope: Envelope<A>) {
+        let letter = DeadLetter::new(self.id, envelope);
+        self.dead_letters.deliver(letter);
+    }
+}
+
+pub struct AddressJoint<A: Agent> {
+    /// High-priority lane for control messages
+    prio_rx: mpsc::UnboundedReceiver<Envelope<A>>,
+    msg_rx: MailboxReceiver<A>,
     status_tx: watch::Sender<AgentStatus<A>>,
+    shared: Arc<Shared>,
+    /// Received envelopes by the type of a message
+    received: HashMap<&'static str, usize>,
+    stashed: VecDeque<Envelope<A>>,
+    /// Envelopes that are taken before the mailbox
+    unstashed: VecDeque<Envelope<A>>,
 }
 
 impl<A: Agent> AddressJoint<A> {
     pub fn new_pair() -> (Address<A>, AddressJoint<A>) {
-        let (msg_tx, msg_rx) = mpsc::unbounded_channel();
+        Self::with_mailbox(mailbox::unbounded())
+    }
+
+    /// Creates a pair with a mailbox that applies backpressure
+    /// to senders when it contains `capacity` envelopes.
+    pub fn new_bounded_pair(capacity: usize) -> (Address<A>, AddressJoint<A>) {
+        Self::with_mailbox(mailbox::bounded(capacity))
+    }
+
+    fn with_mailbox(
+        (msg_tx, msg_rx): (MailboxSender<A>, MailboxReceiver<A>),
+    ) -> (Address<A>, AddressJoint<A>) {
+        let (prio_tx, prio_rx) = mpsc::unbounded_channel();
         let (status_tx, status_rx) = watch::channel(AgentStatus::Active);
-        let address = Address { msg_tx, status_rx };
-        let joint = AddressJoint { msg_rx, status_tx };
+        let shared = Arc::new(Shared {
+            id: Uuid::new_v4(),
+            counter: MailboxCounter::default(),
+            dead_letters: DeadLetterSink::default(),
+            interruptor: OnceLock::new(),
+            ready: watch::Sender::new(false),
+            strong: AtomicUsize::new(0),
+            held: AtomicBool::new(false),
+            on_finished: Mutex::new(Some(Vec::new())),
+        });
+        // The address of the session doesn't keep the agent alive
+        let address = Address {
+            prio_tx,
+            msg_tx,
+            status_rx,
+            shared: shared.clone(),
+            external: false,
+        };
+        let joint = AddressJoint {
+            prio_rx,
+            msg_rx,
+            status_tx,
+            shared,
+            received: HashMap::new(),
+            stashed: VecDeque::new(),
+            unstashed: VecDeque::new(),
+        };
         (address, joint)
     }
 
+    /// Takes the next envelope, draining the priority lane first
+    /// and unstashed envelopes next.
+    ///
+    /// Returns `None` when both lanes are closed and empty.
     pub async fn next_envelope(&mut self) -> Option<Envelope<A>> {
-        self.msg_rx.recv().await
+        poll_fn(|cx| {
+            let prio = self.prio_rx.poll_recv(cx);
+            if let Poll::Ready(Some(envelope)) = prio {
+                return Poll::Ready(Some(self.received(envelope)));
+            }
+            if let Some(envelope) = self.unstashed.pop_front() {
+                return Poll::Ready(Some(envelope));
+            }
+            match self.msg_rx.poll_recv(cx) {
+                Poll::Ready(None) if prio.is_pending() => Poll::Pending,
+                Poll::Ready(Some(envelope)) => Poll::Ready(Some(self.received(envelope))),
+                poll => poll,
+            }
+        })
+        .await
+    }
+
+    /// Closes the mailbox and sends all remaining envelopes to the dead letters sink.
+    pub fn drain(&mut self) {
+        self.close();
+        while let Ok(envelope) = self.prio_rx.try_recv() {
+            self.shared.counter.dequeued();
+            // Releases are internal and not worth reporting
+            if let Err(envelope) = envelope.into_message::<Released>() {
+                self.shared.dead_letter(envelope);
+            }
+        }
+        self.unstash_all();
+        while let Some(envelope) = self.unstashed.pop_front() {
+            self.shared.dead_letter(envelope);
+        }
+        while let Some(envelope) = self.msg_rx.try_recv() {
+            self.shared.counter.dequeued();
+            self.shared.dead_letter(envelope);
+        }
+    }
+
+    pub(crate) fn dead_letters(&self) -> Option<Recipient<DeadLetter>> {
+        self.shared.dead_letters.get()
+    }
+
+    /// Sets a sink for envelopes that can't be delivered to the agent.
+    pub(crate) fn set_dead_letters(&self, sink: Option<Recipient<DeadLetter>>) {
+        self.shared.dead_letters.set(sink);
+    }
+
+    /// Reports that the agent has started and serves requests.
+    pub fn ready(&self) {
+        self.shared
+            .ready
+            .send_if_modified(|ready| !std::mem::replace(ready, true));
+    }
+
+    /// Binds the interruptor of the agent to its addresses.
+    pub(crate) fn set_interruptor(&self, interruptor: Interruptor) {
+        self.shared.interruptor.set(interruptor).ok();
+    }
+
+    fn received(&mut self, envelope: Envelope<A>) -> Envelope<A> {
+        self.shared.counter.dequeued();
+        *self.received.entry(envelope.type_name()).or_default() += 1;
+        envelope
+    }
+
+    /// A snapshot of the mailbox statistics.
+    pub fn stats(&self) -> MailboxStats {
+        MailboxStats {
+            queue_len: self.shared.counter.queue_len(),
+            high_water_mark: self.shared.counter.high_water_mark(),
+            stashed: self.stashed.len(),
+            received: self.received.clone(),
+        }
+    }
+
+    /// Keeps the envelope aside until `unstash_all` is called.
+    pub fn stash(&mut self, envelope: Envelope<A>) {
+        self.stashed.push_back(envelope);
     }
 
-    pub fn report(&mut self, output: Option<A::Output>) -> Result<()> {
-        let status = output
-            .map(AgentStatus::Done)
-            .unwrap_or(AgentStatus::Interrupted);
+    /// Puts all stashed envelopes at the front of the queue in the order they were stashed.
+    pub fn unstash_all(&mut self) {
+        while let Some(envelope) = self.stashed.pop_back() {
+            self.unstashed.push_front(envelope);
+        }
+    }
+
+    pub fn stashed_len(&self) -> usize {
+        self.stashed.len()
+    }
+
+    pub fn report(&mut self, status: AgentStatus<A>) -> Result<()> {
+        // Callbacks are called first, so observers of the status see their effects
+        if status.is_done() {
+            let callbacks = self
+                .shared
+                .on_finished
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            callbacks
+                .into_iter()
+                .flatten()
+                .for_each(|callback| callback());
+        }
         self.status_tx.send(status).map_err(Error::from)
     }
 
     pub fn close(&mut self) {
+        self.prio_rx.close();
         self.msg_rx.close();
     }
 }
 
-pub struct Address<A: Agent + ?Sized> {
-    msg_tx: mpsc::UnboundedSender<Envelope<A>>,
+/// Statistics of an agent's mailbox.
+#[derive(Debug, Clone)]
+pub struct MailboxStats {
+    /// Envelopes waiting in both lanes.
+    pub queue_len: usize,
+    /// The maximal length of the queue that has been reached.
+    pub high_water_mark: usize,
+    pub stashed: usize,
+    /// The number of received envelopes by the type of a message.
+    pub received: HashMap<&'static str, usize>,
+}
+
+pub struct Address<A: Agent> {
+    prio_tx: mpsc::UnboundedSender<Envelope<A>>,
+    msg_tx: MailboxSender<A>,
     status_rx: watch::Receiver<AgentStatus<A>>,
+    shared: Arc<Shared>,
+    /// Counted as a strong address that keeps the agent alive
+    external: bool,
 }
 
 impl<A: Agent> Address<A> {
+    /// Sends a message without waiting.
+    ///
+    /// Fails if the agent is bounded and its mailbox is full.
+    /// The error is `Undelivered<M>` that gives the message back.
     pub fn send(&self, msg: impl MessageFor<A>) -> Result<()> {
-        self.msg_tx
-            .send(Box::new(msg))
-            .map_err(|_| Error::msg("Can't send the message to the actor"))
+        self.send_envelope(msg, None)
+    }
+
+    /// Sends a message with headers that are available to the handler
+    /// with `AgentSession::headers`.
+    ///
+    /// Messages sent by handlers without headers get
+    /// the forwarded headers of the handled message.
+    pub fn send_with(&self, msg: impl MessageFor<A>, headers: Headers) -> Result<()> {
+        self.send_envelope(msg, Some(headers))
+    }
+
+    fn send_envelope<M>(&self, msg: M, headers: Option<Headers>) -> Result<()>
+    where
+        M: MessageFor<A>,
+    {
+        let headers = headers::outgoing(headers);
+        let retained = headers.clone();
+        self.try_send_with(msg, headers).map_err(|err| {
+            let (reason, msg) = match err {
+                TrySendError::Full(msg) => (UndeliveredReason::Full, msg),
+                TrySendError::Closed(msg) => (UndeliveredReason::Closed, msg),
+            };
+            self.undelivered(reason, msg, retained)
+        })
+    }
+
+    /// Wraps the message into an error that sends it
+    /// to the dead letters sink unless it's taken back.
+    fn undelivered<M>(&self, reason: UndeliveredReason, msg: M, headers: Option<Headers>) -> Error
+    where
+        M: MessageFor<A>,
+    {
+        let shared = self.shared.clone();
+        let fallback = move |msg| shared.dead_letter(Envelope::with(msg, headers));
+        Error::new(Undelivered::new(reason, msg, fallback))
+    }
+
+    /// Sends a message to the high-priority lane that is drained before
+    /// the regular mailbox. The lane is never bounded.
+    pub fn send_priority<M: MessageFor<A>>(&self, msg: M) -> Result<()> {
+        if self.prio_tx.is_closed() {
+            return Err(self.undelivered(UndeliveredReason::Closed, msg, None));
+        }
+        self.shared.counter.enqueued();
+        self.prio_tx.send(Envelope::new(msg)).map_err(|err| {
+            self.shared.counter.dequeued();
+            // The lane is closed after the check
+            match err.0.into_message::<M>() {
+                Ok(msg) => self.undelivered(UndeliveredReason::Closed, msg, None),
+                Err(envelope) => {
+                    self.shared.dead_letter(envelope);
+                    Error::msg("Can't send the message to the actor")
+                }
+            }
+        })
+    }
+
+    /// Sends a message and waits for a free slot if the mailbox is bounded.
+    pub async fn send_async<M: MessageFor<A>>(&self, msg: M) -> Result<()> {
+        let headers = headers::outgoing(None);
+        let retained = headers.clone();
+        self.shared.counter.enqueued();
+        self.msg_tx.send(msg, headers).await.map_err(|err| {
+            self.shared.counter.dequeued();
+            self.undelivered(UndeliveredReason::Closed, err.0, retained)
+        })
+    }
+
+    /// Tries to send a message and returns it back if it can't be delivered.
+    pub fn try_send<M>(&self, msg: M) -> Result<(), TrySendError<M>>
+    where
+        M: MessageFor<A>,
+    {
+        self.try_send_with(msg, headers::outgoing(None))
+    }
+
+    fn try_send_with<M>(&self, msg: M, headers: Option<Headers>) -> Result<(), TrySendError<M>>
+    where
+        M: MessageFor<A>,
+    {
+        self.shared.counter.enqueued();
+        self.msg_tx.try_send(msg, headers).inspect_err(|_| {
+            self.shared.counter.dequeued();
+        })
+    }
+
+    /// Waits until the agent reports its readiness.
+    ///
+    /// Fails if the agent has finished without being ready.
+    pub async fn ready(&self) -> Result<()> {
+        let mut ready = self.shared.ready.subscribe();
+        let mut status = self.status_rx.clone();
+        {
+            let ready = pin!(ready.wait_for(|ready| *ready));
+            let done = pin!(status.wait_for(AgentStatus::is_done));
+            select(ready, done).await;
+        }
+        if *self.shared.ready.borrow() {
+            Ok(())
+        } else {
+            Err(Error::msg("The agent has finished before it became ready"))
+        }
+    }
+
+    /// Aborts the routine of the agent immediately
+    /// without handling the remaining messages.
+    pub fn abort(&self) {
+        if let Some(interruptor) = self.shared.interruptor.get() {
+            interruptor.stop(true);
+        }
+    }
+
+    /// The unique id of the agent.
+    pub fn id(&self) -> Uuid {
+        self.shared.id
+    }
+
+    /// The number of queued envelopes including senders
+    /// that are waiting for a free slot of a bounded mailbox.
+    pub fn queue_len(&self) -> usize {
+        self.shared.counter.queue_len()
+    }
+
+    /// The maximal length of the queue that has been reached.
+    pub fn high_water_mark(&self) -> usize {
+        self.shared.counter.high_water_mark()
+    }
+
+    /// The capacity of the mailbox or `None` if it's unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.msg_tx.capacity()
     }
 
     /// Important! `join` must use a reference to allow using it under `DerefMut` trait
@@ -51,6 +389,154 @@ impl<A: Agent> Address<A> {
         let status = self.status_rx.wait_for(AgentStatus::is_done).await?;
         Ok(AgentOutput { status })
     }
+
+    /// Waits for the agent and takes ownership of its output.
+    ///
+    /// The output can be taken only once: after that, `take_output`
+    /// and `AgentOutput::output` return `None` for all addresses,
+    /// while the status remains `Done`.
+    pub async fn take_output(&mut self) -> Result<Option<A::Output>> {
+        Ok(self.join().await?.take())
+    }
+}
+
+impl<A: Agent> Address<A> {
+    /// Makes the agent interruptible by releasing strong addresses.
+    /// Agents that are run in place are never released.
+    pub(crate) fn hold(&self) -> Self {
+        self.shared.held.store(true, Ordering::SeqCst);
+        self.clone()
+    }
+
+    /// Creates an address that doesn't keep the agent alive.
+    pub fn downgrade(&self) -> WeakAddress<A> {
+        WeakAddress {
+            prio_tx: self.prio_tx.downgrade(),
+            msg_tx: self.msg_tx.downgrade(),
+            status_rx: self.status_rx.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// An address that doesn't prevent the agent from terminating
+/// when all strong addresses are dropped.
+pub struct WeakAddress<A: Agent> {
+    prio_tx: mpsc::WeakUnboundedSender<Envelope<A>>,
+    msg_tx: WeakMailboxSender<A>,
+    status_rx: watch::Receiver<AgentStatus<A>>,
+    shared: Arc<Shared>,
+}
+
+impl<A: Agent> WeakAddress<A> {
+    /// Returns a strong address if the mailbox of the agent is still open.
+    pub fn upgrade(&self) -> Option<Address<A>> {
+        let prio_tx = self.prio_tx.upgrade()?;
+        if prio_tx.is_closed() {
+            return None;
+        }
+        Some(Address::external(
+            prio_tx,
+            self.msg_tx.upgrade()?,
+            self.status_rx.clone(),
+            self.shared.clone(),
+        ))
+    }
+
+    /// A temporary address to send a message that doesn't count
+    /// as a strong one, so dropping it never releases the agent.
+    fn reach(&self) -> Option<Address<A>> {
+        let prio_tx = self.prio_tx.upgrade()?;
+        if prio_tx.is_closed() {
+            return None;
+        }
+        Some(Address {
+            prio_tx,
+            msg_tx: self.msg_tx.upgrade()?,
+            status_rx: self.status_rx.clone(),
+            shared: self.shared.clone(),
+            external: false,
+        })
+    }
+
+    /// Sends a message to the priority lane if the agent is still running.
+    pub fn send_priority<M: MessageFor<A>>(&self, msg: M) -> Result<()> {
+        match self.reach() {
+            Some(address) => address.send_priority(msg),
+            None => Err(self.closed(msg)),
+        }
+    }
+
+    fn closed<M: MessageFor<A>>(&self, msg: M) -> Error {
+        let shared = self.shared.clone();
+        let fallback = move |msg| shared.dead_letter(Envelope::new(msg));
+        let undelivered = Undelivered::new(UndeliveredReason::Closed, msg, fallback);
+        Error::new(undelivered)
+    }
+
+    pub fn sender<M>(&self) -> MessageSender<M>
+    where
+        M: MessageFor<A>,
+    {
+        MessageSender::new(self.clone())
+    }
+
+    /// The unique id of the agent.
+    pub fn id(&self) -> Uuid {
+        self.shared.id
+    }
+
+    /// The current status of the agent.
+    pub fn status(&self) -> watch::Ref<'_, AgentStatus<A>> {
+        self.status_rx.borrow()
+    }
+
+    /// Calls the `callback` when the agent reports its finish
+    /// or right away if it has finished already.
+    pub fn on_finished(&self, callback: impl FnOnce() + Send + 'static) {
+        let mut callbacks = self
+            .shared
+            .on_finished
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match callbacks.as_mut() {
+            Some(callbacks) => callbacks.push(Box::new(callback)),
+            None => {
+                drop(callbacks);
+                callback();
+            }
+        }
+    }
+
+    /// Waits for the agent without keeping it alive.
+    pub async fn join(&mut self) -> Result<AgentOutput<'_, A>> {
+        let status = self.status_rx.wait_for(AgentStatus::is_done).await?;
+        Ok(AgentOutput { status })
+    }
+}
+
+impl<A: Agent> Clone for WeakAddress<A> {
+    fn clone(&self) -> Self {
+        Self {
+            prio_tx: self.prio_tx.clone(),
+            msg_tx: self.msg_tx.clone(),
+            status_rx: self.status_rx.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<A, M> Sender<M> for WeakAddress<A>
+where
+    A: Agent,
+    M: MessageFor<A>,
+{
+    fn send(&self, input: M) -> Result<(), Error> {
+        match self.reach() {
+            Some(address) => address.send(input),
+            None => Err(self.closed(input)),
+        }
+    }
 }
 
 pub struct AgentOutput<'a, A: Agent> {
@@ -58,20 +544,93 @@ pub struct AgentOutput<'a, A: Agent> {
 }
 
 impl<'a, A: Agent> AgentOutput<'a, A> {
+    /// Clones the output if it hasn't been taken yet.
+    ///
+    /// Observers that need the output should clone it and never take it.
     pub fn output(&mut self) -> Option<A::Output>
     where
         A::Output: Clone,
     {
-        self.status.output().cloned()
+        self.status.output().and_then(OutputCell::cloned)
+    }
+
+    /// Takes the output out. Only the first call of all observers gets it,
+    /// but the agent remains `Done` for them.
+    pub fn take(&mut self) -> Option<A::Output> {
+        self.status.output().and_then(OutputCell::take)
+    }
+
+    /// The final status that tells why the agent has stopped.
+    pub fn status(&self) -> &AgentStatus<A> {
+        &self.status
+    }
+
+    pub fn error(&self) -> Option<Arc<Error>> {
+        self.status.error().cloned()
+    }
+}
+
+impl<A: Agent> Address<A> {
+    fn external(
+        prio_tx: mpsc::UnboundedSender<Envelope<A>>,
+        msg_tx: MailboxSender<A>,
+        status_rx: watch::Receiver<AgentStatus<A>>,
+        shared: Arc<Shared>,
+    ) -> Self {
+        shared.strong.fetch_add(1, Ordering::SeqCst);
+        Self {
+            prio_tx,
+            msg_tx,
+            status_rx,
+            shared,
+            external: true,
+        }
     }
 }
 
 impl<A: Agent> Clone for Address<A> {
     fn clone(&self) -> Self {
-        Self {
-            msg_tx: self.msg_tx.clone(),
-            status_rx: self.status_rx.clone(),
+        Self::external(
+            self.prio_tx.clone(),
+            self.msg_tx.clone(),
+            self.status_rx.clone(),
+            self.shared.clone(),
+        )
+    }
+}
+
+/// Interrupts the agent when the last strong address is dropped
+/// and the runtime has given a strong address to an owner.
+impl<A: Agent> Drop for Address<A> {
+    fn drop(&mut self) {
+        if self.external
+            && self.shared.strong.fetch_sub(1, Ordering::SeqCst) == 1
+            && self.shared.held.load(Ordering::SeqCst)
+        {
+            let released = Released {
+                shared: self.shared.clone(),
+            };
+            self.shared.counter.enqueued();
+            if self.prio_tx.send(Envelope::new(released)).is_err() {
+                // The agent is finishing already
+                self.shared.counter.dequeued();
+            }
+        }
+    }
+}
+
+struct Released {
+    shared: Arc<Shared>,
+}
+
+#[async_trait]
+impl<A: Agent> MessageFor<A> for Released {
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut A::Context) -> Result<()> {
+        // The agent could be reached by upgrading a weak address meanwhile
+        if self.shared.strong.load(Ordering::SeqCst) == 0 {
+            agent.interrupt(ctx);
         }
+        Ok(())
     }
 }
 
@@ -94,30 +653,215 @@ impl<A: Agent> Address<A> {
     }
 }
 
-#[derive(PartialEq, Eq)]
-pub enum AgentStatus<T: Agent + ?Sized> {
+pub enum AgentStatus<T: Agent> {
     Active,
     Interrupted,
-    Done(T::Output),
+    /// The agent has failed or crashed.
+    Failed(Arc<Error>),
+    Done(OutputCell<T::Output>),
 }
 
 impl<T: Agent> AgentStatus<T> {
     pub fn is_done(&self) -> bool {
-        matches!(self, Self::Interrupted | Self::Done(_))
+        !matches!(self, Self::Active)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+
+    pub fn output(&self) -> Option<&OutputCell<T::Output>> {
+        match self {
+            Self::Done(cell) => Some(cell),
+            _ => None,
+        }
     }
 
-    pub fn output(&self) -> Option<&T::Output> {
+    pub fn output_mut(&mut self) -> Option<&mut T::Output> {
         match self {
-            Self::Active => None,
-            Self::Interrupted => None,
-            Self::Done(value) => Some(value),
+            Self::Done(cell) => cell.get_mut(),
+            _ => None,
+        }
+    }
+
+    pub fn error(&self) -> Option<&Arc<Error>> {
+        match self {
+            Self::Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Failures are equal if they share the same error.
+impl<T: Agent> PartialEq for AgentStatus<T>
+where
+    T::Output: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Active, Self::Active) => true,
+            (Self::Interrupted, Self::Interrupted) => true,
+            (Self::Failed(left), Self::Failed(right)) => Arc::ptr_eq(left, right),
+            (Self::Done(left), Self::Done(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Agent> Eq for AgentStatus<T> where T::Output: Eq {}
+
+impl<T: Agent> From<Option<T::Output>> for AgentStatus<T> {
+    fn from(output: Option<T::Output>) -> Self {
+        output
+            .map(OutputCell::new)
+            .map(Self::Done)
+            .unwrap_or(Self::Interrupted)
+    }
+}
+
+/// Keeps an output of an agent that can be taken only once.
+///
+/// It's shared by all observers of the agent, so taking the output
+/// leaves the cell empty for everyone.
+pub struct OutputCell<T> {
+    value: Mutex<Option<T>>,
+}
+
+impl<T> OutputCell<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Mutex::new(Some(value)),
+        }
+    }
+
+    pub fn take(&self) -> Option<T> {
+        self.value.lock().ok()?.take()
+    }
+
+    pub fn cloned(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.value.lock().ok()?.clone()
+    }
+
+    pub fn is_taken(&self) -> bool {
+        self.value
+            .lock()
+            .map(|value| value.is_none())
+            .unwrap_or(true)
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.get_mut().ok()?.as_mut()
+    }
+}
+
+/// Compares current values, so taken outputs are equal.
+impl<T: PartialEq> PartialEq for OutputCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+        match (self.value.lock(), other.value.lock()) {
+            (Ok(left), Ok(right)) => *left == *right,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Eq> Eq for OutputCell<T> {}
+
+impl<T: Agent> fmt::Debug for AgentStatus<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Active => "Active",
+            Self::Interrupted => "Interrupted",
+            Self::Failed(_) => "Failed(_)",
+            Self::Done(_) => "Done(_)",
+        };
+        write!(f, "AgentStatus::{}", value)
+    }
+}
+
+pub struct Envelope<A: Agent> {
+    type_name: &'static str,
+    headers: Option<Box<Headers>>,
+    message: Box<dyn MessageFor<A>>,
+}
+
+impl<A: Agent> Envelope<A> {
+    pub fn new<M: MessageFor<A>>(message: M) -> Self {
+        Self::with(message, None)
+    }
+
+    pub fn with<M: MessageFor<A>>(message: M, headers: Option<Headers>) -> Self {
+        Self {
+            type_name: message.message_type(),
+            headers: headers.map(Box::new),
+            message: Box::new(message),
+        }
+    }
+
+    /// The type name of the message without wrappers.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn headers(&self) -> Option<&Headers> {
+        self.headers.as_deref()
+    }
+
+    /// Takes the message back if it has the type `M`.
+    pub fn into_message<M: MessageFor<A>>(self) -> Result<M, Self> {
+        if (*self.message).as_any().is::<M>() {
+            match self.message.into_any().downcast() {
+                Ok(message) => Ok(*message),
+                Err(_) => unreachable!("The type of the message is checked"),
+            }
+        } else {
+            Err(self)
         }
     }
+
+    /// Handles the message and keeps its headers in the session meanwhile.
+    ///
+    /// Messages sent by the handler inherit the headers.
+    pub async fn handle(self, agent: &mut A, ctx: &mut A::Context) -> Result<()> {
+        let session = ctx.session();
+        let agent_id = session.address.id();
+        let inherited = self.headers.as_deref().map(Headers::forward);
+        session.headers = self.headers;
+        let result = headers::scoped(agent_id, inherited, self.message.handle(agent, ctx)).await;
+        ctx.session().headers = None;
+        result
+    }
 }
 
-pub type Envelope<A> = Box<dyn MessageFor<A>>;
+/// Converts boxed messages to `Any` to take them back from envelopes.
+pub trait AnyMessage: Send + 'static {
+    fn as_any(&self) -> &dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+}
+
+impl<T: Send + 'static> AnyMessage for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+}
 
 #[async_trait]
-pub trait MessageFor<A: Agent>: Send + 'static {
+pub trait MessageFor<A: Agent>: AnyMessage {
     async fn handle(self: Box<Self>, actor: &mut A, ctx: &mut A::Context) -> Result<()>;
+
+    /// The type name of the message that is used in statistics.
+    /// Wrappers return the type name of the wrapped message.
+    fn message_type(&self) -> &'static str {
+        type_name::<Self>()
+    }
 }
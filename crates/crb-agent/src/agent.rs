@@ -58,7 +58,17 @@ pub trait Standalone: Agent {
     {
         RunAgent::new(self).spawn_connected()
     }
-    // TODO: spawn_with_context()
+
+    fn spawn_bounded(self, capacity: usize) -> <Self::Context as Context>::Address
+    where
+        Self::Context: Default,
+    {
+        RunAgent::new(self).bounded(capacity).spawn_connected()
+    }
+
+    fn spawn_with_context(self, context: Self::Context) -> <Self::Context as Context>::Address {
+        RunAgent::with_context(self, context).spawn_connected()
+    }
 }
 
 #[async_trait]
@@ -70,12 +80,10 @@ pub trait Runnable: Agent {
 impl<A: Agent> Runnable for A
 where
     Self::Context: Default,
-    A::Output: Clone,
 {
     async fn run(self) -> Result<Option<Self::Output>> {
         let mut runtime = RunAgent::new(self);
         runtime.perform_routine().await?;
-        let output = runtime.context.address().clone().join().await?.output();
-        Ok(output)
+        runtime.context.address().clone().take_output().await
     }
 }
@@ -1,10 +1,13 @@
-use crate::address::{Address, AddressJoint};
+use crate::address::{Address, AddressJoint, Envelope, MailboxStats, MessageFor};
 use crate::agent::Agent;
+use crate::dead_letter::DeadLetter;
+use crate::headers::Headers;
 use crate::performers::Next;
 use crb_runtime::{Context, Controller, ManagedContext};
+use crb_send::Recipient;
 use derive_more::{Deref, DerefMut};
 
-pub trait AgentContext<A: Agent + ?Sized>
+pub trait AgentContext<A: Agent>
 where
     Self: Context<Address = Address<A>>,
     Self: ManagedContext,
@@ -14,10 +17,12 @@ where
 }
 
 #[derive(Deref, DerefMut)]
-pub struct AgentSession<A: Agent + ?Sized> {
+pub struct AgentSession<A: Agent> {
     pub controller: Controller,
     pub next_state: Option<Next<A>>,
     pub joint: AddressJoint<A>,
+    /// Headers of the message that is being handled.
+    pub headers: Option<Box<Headers>>,
     #[deref]
     #[deref_mut]
     pub address: Address<A>,
@@ -28,19 +33,76 @@ impl<A: Agent> AgentSession<A> {
         &mut self.joint
     }
 
+    pub fn headers(&self) -> Option<&Headers> {
+        self.headers.as_deref()
+    }
+
     pub fn do_next(&mut self, next_state: Next<A>) {
         self.next_state = Some(next_state);
     }
+
+    /// Defers the envelope until `unstash_all` is called.
+    pub fn stash(&mut self, envelope: Envelope<A>) {
+        self.joint.stash(envelope);
+    }
+
+    /// Defers the message with the headers of the message that is being handled.
+    pub fn stash_message(&mut self, msg: impl MessageFor<A>) {
+        let headers = self.headers().cloned();
+        self.stash(Envelope::with(msg, headers));
+    }
+
+    /// Returns all stashed envelopes to the front of the queue.
+    pub fn unstash_all(&mut self) {
+        self.joint.unstash_all();
+    }
+
+    /// Reports that the agent is ready.
+    ///
+    /// It happens automatically when the agent starts processing events.
+    pub fn ready(&mut self) {
+        self.joint.ready();
+    }
+
+    /// Returns a snapshot of the mailbox statistics.
+    pub fn mailbox_stats(&self) -> MailboxStats {
+        self.joint.stats()
+    }
+
+    /// The sink for messages that can't be delivered to the agent.
+    pub fn dead_letters(&self) -> Option<Recipient<DeadLetter>> {
+        self.joint.dead_letters()
+    }
+
+    /// Sets a sink for messages that can't be delivered to the agent.
+    /// Agents without a sink use the global one.
+    pub fn set_dead_letters(&mut self, sink: Option<Recipient<DeadLetter>>) {
+        self.joint.set_dead_letters(sink);
+    }
+
+    /// Replaces the mailbox with a bounded one.
+    ///
+    /// Must be called before the address of the session is shared,
+    /// because all existing addresses remain bound to the previous mailbox.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let (address, joint) = AddressJoint::new_bounded_pair(capacity);
+        joint.set_dead_letters(self.dead_letters());
+        joint.set_interruptor(self.controller.interruptor.clone());
+        self.address = address;
+        self.joint = joint;
+    }
 }
 
 impl<A: Agent> Default for AgentSession<A> {
     fn default() -> Self {
         let controller = Controller::default();
         let (address, joint) = AddressJoint::new_pair();
+        joint.set_interruptor(controller.interruptor.clone());
         Self {
             controller,
             next_state: None,
             joint,
+            headers: None,
             address,
         }
     }
@@ -0,0 +1,208 @@
+use crate::address::{Envelope, MessageFor};
+use crate::agent::Agent;
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_core::uuid::Uuid;
+use crb_send::{Recipient, Sender};
+use std::any::{type_name, Any};
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+
+static GLOBAL_SINK: Mutex<Option<Recipient<DeadLetter>>> = Mutex::new(None);
+
+/// Sets a sink for dead letters of agents that have no own sink.
+pub fn set_global_sink(sink: Option<Recipient<DeadLetter>>) {
+    if let Ok(mut global) = GLOBAL_SINK.lock() {
+        *global = sink;
+    }
+}
+
+/// An envelope that can't be delivered to the target agent.
+pub struct DeadLetter {
+    target: Uuid,
+    type_name: &'static str,
+    envelope: Box<dyn Any + Send>,
+}
+
+impl DeadLetter {
+    pub(crate) fn new<A: Agent>(target: Uuid, envelope: Envelope<A>) -> Self {
+        Self {
+            target,
+            type_name: envelope.type_name(),
+            envelope: Box::new(envelope),
+        }
+    }
+
+    /// The id of the agent the envelope was addressed to.
+    pub fn target(&self) -> Uuid {
+        self.target
+    }
+
+    /// The type name of the undelivered message.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the envelope if it was addressed to an agent of the type `A`.
+    pub fn into_envelope<A: Agent>(self) -> Result<Envelope<A>, Self> {
+        match self.envelope.downcast() {
+            Ok(envelope) => Ok(*envelope),
+            Err(envelope) => Err(Self { envelope, ..self }),
+        }
+    }
+}
+
+impl fmt::Debug for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("target", &self.target)
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+#[async_trait]
+pub trait OnDeadLetter: Agent {
+    async fn on_dead_letter(&mut self, letter: DeadLetter, ctx: &mut Self::Context) -> Result<()>;
+}
+
+#[async_trait]
+impl<A> MessageFor<A> for DeadLetter
+where
+    A: OnDeadLetter,
+{
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut A::Context) -> Result<()> {
+        agent.on_dead_letter(*self, ctx).await
+    }
+}
+
+/// The reason why a message wasn't delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndeliveredReason {
+    /// The bounded mailbox has no free slots.
+    Full,
+    /// The agent doesn't receive messages anymore.
+    Closed,
+}
+
+type Fallback<M> = Box<dyn FnOnce(M) + Send>;
+
+/// An error that gives an undelivered message back.
+///
+/// If the message is not taken with `into_message`,
+/// it's sent to the dead letters sink when the error is dropped.
+pub struct Undelivered<M> {
+    reason: UndeliveredReason,
+    // The mutex makes the error `Sync` for `anyhow`
+    message: Mutex<Option<(M, Fallback<M>)>>,
+}
+
+impl<M: Send + 'static> Undelivered<M> {
+    pub(crate) fn new(
+        reason: UndeliveredReason,
+        message: M,
+        fallback: impl FnOnce(M) + Send + 'static,
+    ) -> Self {
+        Self {
+            reason,
+            message: Mutex::new(Some((message, Box::new(fallback)))),
+        }
+    }
+
+    pub fn reason(&self) -> UndeliveredReason {
+        self.reason
+    }
+
+    /// Takes the message back.
+    pub fn into_message(mut self) -> M {
+        let (message, _) = self.take().expect("The message is taken only once");
+        message
+    }
+
+    /// Converts the message keeping the way back to the dead letters sink.
+    pub(crate) fn map<N>(
+        mut self,
+        into: impl FnOnce(M) -> N,
+        back: impl FnOnce(N) -> M + Send + 'static,
+    ) -> Undelivered<N>
+    where
+        N: Send + 'static,
+    {
+        let (message, fallback) = self.take().expect("The message is taken only once");
+        Undelivered::new(self.reason, into(message), move |msg| fallback(back(msg)))
+    }
+
+    fn take(&mut self) -> Option<(M, Fallback<M>)> {
+        self.message
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+impl<M> Drop for Undelivered<M> {
+    fn drop(&mut self) {
+        let slot = self
+            .message
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((message, fallback)) = slot.take() {
+            fallback(message);
+        }
+    }
+}
+
+impl<M> fmt::Debug for Undelivered<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Undelivered")
+            .field("type_name", &type_name::<M>())
+            .field("reason", &self.reason)
+            .finish()
+    }
+}
+
+impl<M> fmt::Display for Undelivered<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            UndeliveredReason::Full => write!(f, "The mailbox of the actor is full"),
+            UndeliveredReason::Closed => write!(f, "Can't send the message to the actor"),
+        }
+    }
+}
+
+impl<M> StdError for Undelivered<M> {}
+
+/// A sink of an agent that falls back to the global one.
+#[derive(Default)]
+pub(crate) struct DeadLetterSink {
+    sink: Mutex<Option<Recipient<DeadLetter>>>,
+}
+
+impl DeadLetterSink {
+    pub fn get(&self) -> Option<Recipient<DeadLetter>> {
+        self.sink.lock().ok()?.clone()
+    }
+
+    pub fn set(&self, sink: Option<Recipient<DeadLetter>>) {
+        if let Ok(mut local) = self.sink.lock() {
+            *local = sink;
+        }
+    }
+
+    pub fn deliver(&self, letter: DeadLetter) {
+        // Undelivered dead letters are dropped to avoid loops
+        if letter.type_name == type_name::<DeadLetter>() {
+            log::warn!("Dead letter to {} has been lost", letter.target);
+            return;
+        }
+        let sink = self
+            .get()
+            .or_else(|| GLOBAL_SINK.lock().ok().and_then(|global| global.clone()));
+        if let Some(sink) = sink {
+            if let Err(err) = sink.send(letter) {
+                log::error!("Can't send a dead letter to the sink: {err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crb_core::{time::Instant, uuid::Uuid};
+use futures::Future;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Optional metadata that travels next to a message.
+#[derive(Debug, Clone, Default)]
+pub struct Headers {
+    /// Links messages that belong to the same flow.
+    pub correlation_id: Option<Uuid>,
+    /// Filled automatically when the message is sent.
+    pub timestamp: Option<Instant>,
+    /// An identity of the sender. Filled with the id of the agent
+    /// if it's not set and the message is sent by a handler.
+    pub sender: Option<String>,
+    pub tags: HashMap<String, String>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a new random correlation id.
+    pub fn correlated() -> Self {
+        Self::new().correlation_id(Uuid::new_v4())
+    }
+
+    pub fn correlation_id(mut self, id: Uuid) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
+    pub fn sender(mut self, sender: impl ToString) -> Self {
+        self.sender = Some(sender.to_string());
+        self
+    }
+
+    pub fn tag(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn get_tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(String::as_str)
+    }
+
+    /// Headers for a message that continues the same flow.
+    ///
+    /// Keeps the correlation id and tags, but resets the sender and the timestamp.
+    pub fn forward(&self) -> Self {
+        Self {
+            correlation_id: self.correlation_id,
+            timestamp: None,
+            sender: None,
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Headers that messages sent by the current handler inherit.
+    pub fn inherited() -> Option<Self> {
+        SCOPE.with_borrow(|scope| scope.as_ref()?.headers.clone())
+    }
+}
+
+/// The agent that handles a message and the headers for messages it sends.
+struct Scope {
+    agent: Uuid,
+    headers: Option<Headers>,
+}
+
+thread_local! {
+    static SCOPE: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+
+/// Completes the headers of an outgoing message.
+///
+/// Inside a handler a message without headers gets the forwarded
+/// headers of the handled message.
+pub(crate) fn outgoing(headers: Option<Headers>) -> Option<Headers> {
+    SCOPE.with_borrow(|scope| {
+        let mut headers = headers.or_else(|| scope.as_ref()?.headers.clone())?;
+        headers.timestamp = Some(Instant::now());
+        if headers.sender.is_none() {
+            headers.sender = scope.as_ref().map(|scope| scope.agent.to_string());
+        }
+        Some(headers)
+    })
+}
+
+/// Keeps the scope while the handler is polled.
+pub(crate) fn scoped<F>(agent: Uuid, headers: Option<Headers>, fut: F) -> Scoped<F>
+where
+    F: Future + Unpin,
+{
+    let scope = Some(Scope { agent, headers });
+    Scoped { scope, fut }
+}
+
+pub(crate) struct Scoped<F> {
+    scope: Option<Scope>,
+    fut: F,
+}
+
+impl<F: Future + Unpin> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let outer = SCOPE.replace(this.scope.take());
+        let poll = Pin::new(&mut this.fut).poll(cx);
+        this.scope = SCOPE.replace(outer);
+        poll
+    }
+}
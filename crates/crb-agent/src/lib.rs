@@ -1,18 +1,25 @@
 pub mod address;
 pub mod agent;
 pub mod context;
+pub mod dead_letter;
 pub mod equip;
 pub mod finalizer;
+pub mod headers;
+pub mod mailbox;
 pub mod message;
 pub mod performers;
 pub mod runtime;
 
-pub use address::{Address, MessageFor};
+pub use address::{Address, MailboxStats, MessageFor, WeakAddress};
 pub use agent::{Agent, Runnable, Standalone};
 pub use context::{AgentContext, AgentSession};
+pub use dead_letter::{DeadLetter, OnDeadLetter, Undelivered, UndeliveredReason};
 pub use equip::Equip;
+pub use headers::Headers;
 pub use message::event::OnEvent;
+pub use performers::async_events_performer::DoAsyncWithEvents;
 pub use performers::async_performer::DoAsync;
+pub use performers::consume_performer::Consume;
 pub use performers::duty_performer::Duty;
 pub use performers::Next;
 pub use runtime::RunAgent;
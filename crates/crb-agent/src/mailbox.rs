@@ -0,0 +1,186 @@
+use crate::address::{Envelope, MessageFor};
+use crate::agent::Agent;
+use crate::headers::Headers;
+use crb_core::mpsc::{
+    self,
+    error::{SendError, TrySendError},
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+pub fn unbounded<A: Agent>() -> (MailboxSender<A>, MailboxReceiver<A>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (MailboxSender::Unbounded(tx), MailboxReceiver::Unbounded(rx))
+}
+
+/// Creates a mailbox that can't keep more than `capacity` envelopes.
+///
+/// # Panics
+///
+/// Panics if the `capacity` is zero.
+pub fn bounded<A: Agent>(capacity: usize) -> (MailboxSender<A>, MailboxReceiver<A>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (MailboxSender::Bounded(tx), MailboxReceiver::Bounded(rx))
+}
+
+pub enum MailboxSender<A: Agent> {
+    Unbounded(mpsc::UnboundedSender<Envelope<A>>),
+    Bounded(mpsc::Sender<Envelope<A>>),
+}
+
+impl<A: Agent> Clone for MailboxSender<A> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Unbounded(tx) => Self::Unbounded(tx.clone()),
+            Self::Bounded(tx) => Self::Bounded(tx.clone()),
+        }
+    }
+}
+
+impl<A: Agent> MailboxSender<A> {
+    pub fn capacity(&self) -> Option<usize> {
+        match self {
+            Self::Unbounded(_) => None,
+            Self::Bounded(tx) => Some(tx.max_capacity()),
+        }
+    }
+
+    pub fn downgrade(&self) -> WeakMailboxSender<A> {
+        match self {
+            Self::Unbounded(tx) => WeakMailboxSender::Unbounded(tx.downgrade()),
+            Self::Bounded(tx) => WeakMailboxSender::Bounded(tx.downgrade()),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match self {
+            Self::Unbounded(tx) => tx.is_closed(),
+            Self::Bounded(tx) => tx.is_closed(),
+        }
+    }
+
+    /// Puts a message to the mailbox without waiting.
+    pub fn try_send<M>(&self, msg: M, headers: Option<Headers>) -> Result<(), TrySendError<M>>
+    where
+        M: MessageFor<A>,
+    {
+        match self {
+            Self::Unbounded(tx) => {
+                if tx.is_closed() {
+                    return Err(TrySendError::Closed(msg));
+                }
+                // The channel could be closed right after the check
+                tx.send(Envelope::with(msg, headers))
+                    .map_err(|err| match err.0.into_message() {
+                        Ok(msg) => TrySendError::Closed(msg),
+                        Err(_) => unreachable!("The envelope has the sent message"),
+                    })
+            }
+            Self::Bounded(tx) => match tx.try_reserve() {
+                Ok(permit) => {
+                    permit.send(Envelope::with(msg, headers));
+                    Ok(())
+                }
+                Err(TrySendError::Full(())) => Err(TrySendError::Full(msg)),
+                Err(TrySendError::Closed(())) => Err(TrySendError::Closed(msg)),
+            },
+        }
+    }
+
+    /// Waits for a free slot in the mailbox and puts a message into it.
+    pub async fn send<M>(&self, msg: M, headers: Option<Headers>) -> Result<(), SendError<M>>
+    where
+        M: MessageFor<A>,
+    {
+        match self {
+            Self::Unbounded(_) => self
+                .try_send(msg, headers)
+                .map_err(|err| SendError(err.into_inner())),
+            Self::Bounded(tx) => match tx.reserve().await {
+                Ok(permit) => {
+                    permit.send(Envelope::with(msg, headers));
+                    Ok(())
+                }
+                Err(_) => Err(SendError(msg)),
+            },
+        }
+    }
+}
+
+/// A sender that doesn't keep the mailbox open.
+pub enum WeakMailboxSender<A: Agent> {
+    Unbounded(mpsc::WeakUnboundedSender<Envelope<A>>),
+    Bounded(mpsc::WeakSender<Envelope<A>>),
+}
+
+impl<A: Agent> Clone for WeakMailboxSender<A> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Unbounded(tx) => Self::Unbounded(tx.clone()),
+            Self::Bounded(tx) => Self::Bounded(tx.clone()),
+        }
+    }
+}
+
+impl<A: Agent> WeakMailboxSender<A> {
+    pub fn upgrade(&self) -> Option<MailboxSender<A>> {
+        match self {
+            Self::Unbounded(tx) => tx.upgrade().map(MailboxSender::Unbounded),
+            Self::Bounded(tx) => tx.upgrade().map(MailboxSender::Bounded),
+        }
+    }
+}
+
+pub enum MailboxReceiver<A: Agent> {
+    Unbounded(mpsc::UnboundedReceiver<Envelope<A>>),
+    Bounded(mpsc::Receiver<Envelope<A>>),
+}
+
+impl<A: Agent> MailboxReceiver<A> {
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Envelope<A>>> {
+        match self {
+            Self::Unbounded(rx) => rx.poll_recv(cx),
+            Self::Bounded(rx) => rx.poll_recv(cx),
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<Envelope<A>> {
+        match self {
+            Self::Unbounded(rx) => rx.try_recv().ok(),
+            Self::Bounded(rx) => rx.try_recv().ok(),
+        }
+    }
+
+    pub fn close(&mut self) {
+        match self {
+            Self::Unbounded(rx) => rx.close(),
+            Self::Bounded(rx) => rx.close(),
+        }
+    }
+}
+
+/// Counters of a mailbox shared between addresses and the joint.
+#[derive(Debug, Default)]
+pub struct MailboxCounter {
+    queued: AtomicUsize,
+    high_water_mark: AtomicUsize,
+}
+
+impl MailboxCounter {
+    pub fn queue_len(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn enqueued(&self) {
+        let len = self.queued.fetch_add(1, Ordering::Relaxed) + 1;
+        self.high_water_mark.fetch_max(len, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dequeued(&self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
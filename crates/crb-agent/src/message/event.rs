@@ -1,8 +1,11 @@
-use crate::address::{Address, MessageFor};
+use crate::address::{Address, MessageFor, WeakAddress};
 use crate::agent::Agent;
+use crate::dead_letter::Undelivered;
+use crate::headers::Headers;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use crb_send::Recipient;
+use crb_send::{Recipient, Sender};
+use std::any::type_name;
 
 impl<A: Agent> Address<A> {
     pub fn event<E>(&self, event: E) -> Result<()>
@@ -10,15 +13,58 @@ impl<A: Agent> Address<A> {
         A: OnEvent<E>,
         E: Send + 'static,
     {
-        self.send(Event::new(event))
+        self.send(Event::new(event)).map_err(undelivered_event::<E>)
     }
 
+    pub fn event_with<E>(&self, event: E, headers: Headers) -> Result<()>
+    where
+        A: OnEvent<E>,
+        E: Send + 'static,
+    {
+        self.send_with(Event::new(event), headers)
+            .map_err(undelivered_event::<E>)
+    }
+
+    pub fn recipient<E>(&self) -> Recipient<E>
+    where
+        A: OnEvent<E>,
+        E: Send + 'static,
+    {
+        Recipient::new(EventSender(self.clone()))
+    }
+}
+
+impl<A: Agent> WeakAddress<A> {
+    /// A recipient that doesn't keep the agent alive.
     pub fn recipient<E>(&self) -> Recipient<E>
     where
         A: OnEvent<E>,
         E: Send + 'static,
     {
-        Recipient::new(self.clone()).reform(Event::new)
+        Recipient::new(EventSender(self.clone()))
+    }
+}
+
+/// Sends events and gives them back if they are undelivered.
+struct EventSender<T>(T);
+
+impl<T, E> Sender<E> for EventSender<T>
+where
+    T: Sender<Event<E>>,
+    E: Send + 'static,
+{
+    fn send(&self, event: E) -> Result<()> {
+        self.0
+            .send(Event::new(event))
+            .map_err(undelivered_event::<E>)
+    }
+}
+
+/// Gives the event back instead of its wrapper.
+fn undelivered_event<E: Send + 'static>(err: Error) -> Error {
+    match err.downcast::<Undelivered<Event<E>>>() {
+        Ok(undelivered) => Error::new(undelivered.map(|event| event.event, Event::new)),
+        Err(err) => err,
     }
 }
 
@@ -42,6 +88,10 @@ impl<E> Event<E> {
     pub fn new(event: E) -> Self {
         Self { event }
     }
+
+    pub fn into_inner(self) -> E {
+        self.event
+    }
 }
 
 #[async_trait]
@@ -57,4 +107,8 @@ where
             Ok(())
         }
     }
+
+    fn message_type(&self) -> &'static str {
+        type_name::<E>()
+    }
 }
@@ -3,10 +3,24 @@ use crate::address::MessageFor;
 use crate::agent::Agent;
 use anyhow::Result;
 use async_trait::async_trait;
+use crb_core::time::{timeout, Duration};
 
 impl<A: Agent> Address<A> {
     pub fn interrupt(&self) -> Result<()> {
-        self.send(Interrupt)
+        self.send_priority(Interrupt)
+    }
+
+    /// Interrupts the agent gracefully and aborts it
+    /// if it's still alive when the `deadline` passes.
+    pub async fn interrupt_within(&mut self, deadline: Duration) -> Result<()> {
+        // The mailbox is closed already if the agent is finishing
+        self.interrupt().ok();
+        let elapsed = timeout(Some(deadline), self.join()).await.is_err();
+        if elapsed {
+            self.abort();
+            self.join().await?;
+        }
+        Ok(())
     }
 }
 
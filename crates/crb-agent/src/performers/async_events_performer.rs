@@ -0,0 +1,91 @@
+use crate::agent::Agent;
+use crate::context::AgentContext;
+use crate::performers::{AgentState, Next, StatePerformer, Transition, TransitionCommand};
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb_runtime::Interruptor;
+use futures::future::{select, Either};
+use std::marker::PhantomData;
+
+impl<T> Next<T>
+where
+    T: Agent,
+{
+    /// Performs a routine with the state while the agent handles incoming messages.
+    pub fn do_async_with_events<S>(state: S) -> Self
+    where
+        T: DoAsyncWithEvents<S>,
+        S: AgentState,
+    {
+        let performer = AsyncEventsPerformer {
+            _task: PhantomData,
+            state: Some(state),
+        };
+        Self::new(performer)
+    }
+}
+
+/// A routine that has access to its state only, because the agent
+/// remains available for handlers of incoming messages meanwhile.
+///
+/// If a handler sets the next state with `do_next`, the routine is dropped
+/// and the agent switches to that state. If the mailbox is closed the
+/// routine is dropped and the agent is interrupted.
+#[async_trait]
+pub trait DoAsyncWithEvents<S: Send + 'static = ()>: Agent {
+    async fn perform(state: &mut S, interruptor: Interruptor) -> Result<()>;
+
+    async fn complete(&mut self, _state: S, _ctx: &mut Self::Context) -> Result<Next<Self>> {
+        Ok(Next::done())
+    }
+
+    async fn fallback(&mut self, err: Error, _ctx: &mut Self::Context) -> Next<Self> {
+        Next::fail(err)
+    }
+}
+
+struct AsyncEventsPerformer<T, S> {
+    _task: PhantomData<T>,
+    state: Option<S>,
+}
+
+#[async_trait]
+impl<T, S> StatePerformer<T> for AsyncEventsPerformer<T, S>
+where
+    T: DoAsyncWithEvents<S>,
+    S: AgentState,
+{
+    async fn perform(&mut self, mut agent: T, ctx: &mut T::Context) -> Transition<T> {
+        let interruptor = ctx.session().controller.interruptor.clone();
+        let mut state = self.state.take().unwrap();
+        let mut routine = T::perform(&mut state, interruptor);
+        let result = loop {
+            let next_envelope = Box::pin(ctx.session().joint().next_envelope());
+            let envelope = match select(&mut routine, next_envelope).await {
+                Either::Left((result, _)) => break Some(result),
+                Either::Right((envelope, _)) => envelope,
+            };
+            let Some(envelope) = envelope else {
+                break None;
+            };
+            if let Err(err) = envelope.handle(&mut agent, ctx).await {
+                agent.failed(&err, ctx);
+            }
+            if let Some(next_state) = ctx.session().next_state.take() {
+                let command = TransitionCommand::Next(next_state);
+                return Transition::Continue { agent, command };
+            }
+        };
+        drop(routine);
+        let next_state = match result {
+            Some(Ok(())) => match agent.complete(state, ctx).await {
+                Ok(next_state) => next_state,
+                Err(err) => agent.fallback(err, ctx).await,
+            },
+            Some(Err(err)) => agent.fallback(err, ctx).await,
+            None => Next::interrupt(),
+        };
+        let command = TransitionCommand::Next(next_state);
+        Transition::Continue { agent, command }
+    }
+}
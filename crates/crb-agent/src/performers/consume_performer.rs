@@ -1,4 +1,56 @@
-// TODO: Add
-// Next::output() - provides an output directly
+use crate::agent::Agent;
+use crate::performers::{ConsumptionReason, Next, StatePerformer, Transition};
+use async_trait::async_trait;
 
-// Next::consume() - calls `Consume(self)` trait -> Output
+impl<A> Next<A>
+where
+    A: Agent,
+{
+    /// Provides an output directly and finishes the agent.
+    pub fn output(output: A::Output) -> Self {
+        Self::new(OutputPerformer {
+            output: Some(output),
+        })
+    }
+
+    /// Consumes the agent with the `Consume` trait to get an output.
+    pub fn consume() -> Self
+    where
+        A: Consume,
+    {
+        Self::new(ConsumePerformer)
+    }
+}
+
+pub trait Consume: Agent {
+    fn consume(self) -> Self::Output;
+}
+
+pub struct OutputPerformer<O> {
+    output: Option<O>,
+}
+
+#[async_trait]
+impl<A> StatePerformer<A> for OutputPerformer<A::Output>
+where
+    A: Agent,
+{
+    async fn perform(&mut self, _agent: A, _session: &mut A::Context) -> Transition<A> {
+        let reason = ConsumptionReason::Transformed(self.output.take());
+        Transition::Consume { reason }
+    }
+}
+
+pub struct ConsumePerformer;
+
+#[async_trait]
+impl<A> StatePerformer<A> for ConsumePerformer
+where
+    A: Consume,
+{
+    async fn perform(&mut self, agent: A, _session: &mut A::Context) -> Transition<A> {
+        let output = agent.consume();
+        let reason = ConsumptionReason::Transformed(Some(output));
+        Transition::Consume { reason }
+    }
+}
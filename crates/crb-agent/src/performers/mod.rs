@@ -1,3 +1,4 @@
+pub mod async_events_performer;
 pub mod async_performer;
 pub mod consume_performer;
 pub mod duty_performer;
@@ -23,7 +24,7 @@ pub struct Next<T: ?Sized> {
 
 impl<T> Next<T>
 where
-    T: Agent + ?Sized,
+    T: Agent,
 {
     pub fn new(performer: impl StatePerformer<T>) -> Self {
         Self {
@@ -32,14 +33,14 @@ where
     }
 }
 
-pub enum TransitionCommand<T> {
+pub enum TransitionCommand<T: Agent> {
     Next(Next<T>),
     Stop(StopReason),
     ProcessEvents,
     InContext(Envelope<T>),
 }
 
-impl<T> fmt::Debug for TransitionCommand<T> {
+impl<T: Agent> fmt::Debug for TransitionCommand<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let value = match self {
             Self::Next(_) => "Next(_)",
@@ -85,7 +86,7 @@ impl<O> fmt::Debug for ConsumptionReason<O> {
     }
 }
 
-pub enum Transition<T: Agent + ?Sized> {
+pub enum Transition<T: Agent> {
     Continue {
         agent: T,
         command: TransitionCommand<T>,
@@ -109,6 +110,6 @@ impl<T: Agent> fmt::Debug for Transition<T> {
 }
 
 #[async_trait]
-pub trait StatePerformer<T: Agent + ?Sized>: Send + 'static {
+pub trait StatePerformer<T: Agent>: Send + 'static {
     async fn perform(&mut self, agent: T, session: &mut T::Context) -> Transition<T>;
 }
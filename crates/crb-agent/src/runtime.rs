@@ -1,3 +1,4 @@
+use crate::address::AgentStatus;
 use crate::agent::Agent;
 use crate::context::AgentContext;
 use crate::finalizer::FinalizerFor;
@@ -8,7 +9,8 @@ use crb_runtime::{
     Context, Failures, InteractiveRuntime, InteractiveTask, Interruptor, ManagedContext, Runtime,
     Task,
 };
-use futures::stream::Abortable;
+use futures::stream::{Abortable, Aborted};
+use std::sync::Arc;
 
 pub struct RunAgent<A: Agent> {
     pub agent: Option<A>,
@@ -22,37 +24,58 @@ impl<A: Agent> RunAgent<A> {
     where
         A::Context: Default,
     {
+        Self::with_context(agent, A::Context::default())
+    }
+
+    pub fn with_context(agent: A, context: A::Context) -> Self {
         Self {
             agent: Some(agent),
-            context: A::Context::default(),
+            context,
             failures: Failures::default(),
             finalizers: Vec::new(),
         }
     }
+
+    /// Limits the mailbox of the agent with the `capacity`.
+    pub fn bounded(mut self, capacity: usize) -> Self {
+        self.context.session().set_capacity(capacity);
+        self
+    }
 }
 
 impl<T: Agent> RunAgent<T> {
     pub(crate) async fn perform_routine(&mut self) -> Result<()> {
-        let reg = self.context.session().controller.take_registration()?;
-        let fut = self.perform_task();
-        let output = Abortable::new(fut, reg).await??;
-        if let Some(output) = output.as_ref() {
+        let status = self.perform_abortable().await;
+        // Observers get the status after leftovers are in dead letters
+        self.context.session().joint().drain();
+        let mut status = status?;
+        if let Some(output) = status.output_mut() {
             for finalizer in &mut self.finalizers {
                 let res = finalizer.finalize(output);
                 self.failures.put(res);
             }
         }
-        self.context.session().joint.report(output)?;
+        self.context.session().joint.report(status)?;
         Ok(())
     }
 
-    async fn perform_task(&mut self) -> Result<Option<T::Output>> {
+    async fn perform_abortable(&mut self) -> Result<AgentStatus<T>> {
+        let reg = self.context.session().controller.take_registration()?;
+        let fut = self.perform_task();
+        match Abortable::new(fut, reg).await {
+            Ok(status) => status,
+            Err(Aborted) => Ok(AgentStatus::Interrupted),
+        }
+    }
+
+    async fn perform_task(&mut self) -> Result<AgentStatus<T>> {
         if let Some(mut agent) = self.agent.take() {
             // let session = self.context.session();
 
             // Initialize
             let initial_state = agent.initialize(&mut self.context);
             let mut pair = (agent, Some(initial_state));
+            let mut failure = None;
 
             // Events or States
             while self.context.session().is_alive() {
@@ -68,12 +91,14 @@ impl<T: Agent> RunAgent<T> {
                                 pair = (agent, Some(next_state));
                             }
                             TransitionCommand::ProcessEvents => {
+                                self.context.session().ready();
                                 pair = (agent, None);
                             }
                             TransitionCommand::Stop(reason) => {
                                 match reason {
                                     StopReason::Failed(err) => {
                                         agent.failed(&err, &mut self.context);
+                                        failure = Some(err);
                                     }
                                     StopReason::Interrupted | StopReason::Done => {}
                                 }
@@ -91,10 +116,10 @@ impl<T: Agent> RunAgent<T> {
                         },
                         Transition::Consume { reason } => match reason {
                             ConsumptionReason::Transformed(output) => {
-                                return Ok(output);
+                                return Ok(AgentStatus::from(output));
                             }
                             ConsumptionReason::Crashed(err) => {
-                                return Err(err);
+                                return Ok(AgentStatus::Failed(Arc::new(err)));
                             }
                         },
                     }
@@ -112,7 +137,11 @@ impl<T: Agent> RunAgent<T> {
             // Finalize
             let agent = pair.0;
             let output = agent.finalize(&mut self.context);
-            Ok(output)
+            if let Some(err) = failure {
+                Ok(AgentStatus::Failed(Arc::new(err)))
+            } else {
+                Ok(AgentStatus::from(output))
+            }
         } else {
             Err(Error::msg("Agent's agent has consumed already."))
         }
@@ -142,6 +171,6 @@ impl<A: Agent> InteractiveRuntime for RunAgent<A> {
     type Context = A::Context;
 
     fn address(&self) -> <Self::Context as Context>::Address {
-        self.context.address().clone()
+        self.context.address().hold()
     }
 }
@@ -4,40 +4,71 @@ use crate::stage::{Stage, StageDestination, StageKey, StageSource};
 use async_trait::async_trait;
 use crb_agent::{Address, Agent, RunAgent};
 use crb_runtime::{Interruptor, Runtime};
+use std::sync::Arc;
 
 pub mod stage {
     use super::*;
 
     pub type Agent<A> = AgentStage<A>;
 
+    /// A stage of agents with default contexts.
+    /// Use `AgentStage::with_context` for other contexts.
     pub fn agent<A>() -> AgentStage<A>
     where
-        A: Stage,
+        A: crb_agent::Agent + Stage,
         A::Config: Default,
+        A::Context: Default,
     {
         AgentStage::<A>::default()
     }
 }
 
-pub struct AgentStage<A: Stage> {
+/// A factory of contexts for every spawned agent of a stage.
+pub type ContextFactory<A> = Arc<dyn Fn() -> <A as Agent>::Context + Send + Sync>;
+
+pub struct AgentStage<A: Agent + Stage> {
     config: A::Config,
+    context: ContextFactory<A>,
 }
 
 impl<A> Default for AgentStage<A>
 where
-    A: Stage,
+    A: Agent + Stage,
     A::Config: Default,
+    A::Context: Default,
 {
     fn default() -> Self {
+        Self::new(A::Config::default())
+    }
+}
+
+impl<A> AgentStage<A>
+where
+    A: Agent + Stage,
+{
+    /// Creates a stage that spawns agents with default contexts.
+    pub fn new(config: A::Config) -> Self
+    where
+        A::Context: Default,
+    {
+        Self::with_context(config, A::Context::default)
+    }
+
+    /// Creates a stage that spawns agents with contexts made by the `factory`.
+    pub fn with_context<F>(config: A::Config, factory: F) -> Self
+    where
+        F: Fn() -> A::Context + Send + Sync + 'static,
+    {
         Self {
-            config: A::Config::default(),
+            config,
+            context: Arc::new(factory),
         }
     }
 }
 
 impl<A> StageSource for AgentStage<A>
 where
-    A: Stage,
+    A: Agent + Stage,
 {
     type Stage = A;
     type Key = StageKey<A>;
@@ -50,12 +81,12 @@ where
 impl<A> StageDestination for AgentStage<A>
 where
     A: Agent + Stage,
-    A::Context: Default,
 {
     type Stage = A;
 
     fn destination(&self) -> RoutePoint<A::Input, A::State> {
-        let generator = AgentStageRuntimeGenerator::<A>::generator(self.config.clone());
+        let generator =
+            AgentStageRuntimeGenerator::<A>::generator(self.config.clone(), self.context.clone());
         RoutePoint::new(generator)
     }
 }
@@ -70,7 +101,6 @@ pub struct AgentStageRuntime<A: Agent + Stage> {
 impl<A> Runtime for AgentStageRuntime<A>
 where
     A: Agent + Stage,
-    A::Context: Default,
 {
     fn get_interruptor(&mut self) -> Interruptor {
         self.runtime.get_interruptor()
@@ -89,29 +119,28 @@ where
     }
 }
 
-pub struct AgentStageRuntimeGenerator<A: Stage> {
+pub struct AgentStageRuntimeGenerator<A: Agent + Stage> {
     config: A::Config,
+    context: ContextFactory<A>,
 }
 
 impl<A> AgentStageRuntimeGenerator<A>
 where
     A: Agent + Stage,
 {
-    pub fn generator(config: A::Config) -> impl RuntimeGenerator<Input = A::Input, State = A::State>
-    where
-        A: Stage,
-        A::Context: Default,
-    {
-        Self { config }
+    pub fn generator(
+        config: A::Config,
+        context: ContextFactory<A>,
+    ) -> impl RuntimeGenerator<Input = A::Input, State = A::State> {
+        Self { config, context }
     }
 }
 
-unsafe impl<A: Stage> Sync for AgentStageRuntimeGenerator<A> {}
+unsafe impl<A: Agent + Stage> Sync for AgentStageRuntimeGenerator<A> {}
 
 impl<A> RuntimeGenerator for AgentStageRuntimeGenerator<A>
 where
     A: Agent + Stage,
-    A::Context: Default,
 {
     type State = A::State;
     type Input = A::Input;
@@ -125,7 +154,8 @@ where
     ) -> Box<dyn Runtime> {
         let config = self.config.clone();
         let instance = A::construct(config, input, state);
-        let runtime = RunAgent::new(instance);
+        let context = (self.context)();
+        let runtime = RunAgent::with_context(instance, context);
         let conducted_runtime = AgentStageRuntime::<A> {
             meta,
             pipeline,
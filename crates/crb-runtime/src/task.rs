@@ -21,11 +21,7 @@ pub trait Task<T = ()>: Runtime + Sized {
         let handle = crb_core::spawn(async move {
             self.routine().await;
         });
-        let job = JobHandle {
-            interruptor,
-            handle,
-            cancel_on_drop: false,
-        };
+        let job = JobHandle::new(interruptor, handle);
         TaskHandle {
             job,
             _task: PhantomData,
@@ -64,6 +60,18 @@ pub struct JobHandle {
 }
 
 impl JobHandle {
+    pub fn new(interruptor: Interruptor, handle: JoinHandle<()>) -> Self {
+        Self {
+            interruptor,
+            handle,
+            cancel_on_drop: false,
+        }
+    }
+
+    pub fn interruptor(&self) -> &Interruptor {
+        &self.interruptor
+    }
+
     pub fn cancel_on_drop(&mut self, cancel: bool) {
         self.cancel_on_drop = cancel;
     }
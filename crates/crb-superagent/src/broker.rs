@@ -0,0 +1,261 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_agent::{
+    Address, Agent, AgentSession, MessageFor, OnEvent, Standalone, Undelivered, UndeliveredReason,
+};
+use crb_core::uuid::Uuid;
+use crb_core::JoinHandle;
+use crb_send::{Recipient, Sender};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// A message that can be published to a topic.
+pub trait TopicMessage: Clone + Send + 'static {}
+
+impl<M> TopicMessage for M where M: Clone + Send + 'static {}
+
+/// An agent that delivers published messages to subscribers of typed topics.
+#[derive(Default)]
+pub struct Broker {
+    topics: HashMap<(TypeId, String), Box<dyn Any + Send>>,
+}
+
+impl Standalone for Broker {}
+
+impl Agent for Broker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+impl Broker {
+    fn topic<M: TopicMessage>(&mut self, name: String) -> &mut Topic<M> {
+        self.topics
+            .entry((TypeId::of::<M>(), name))
+            .or_insert_with(|| Box::new(Topic::<M>::default()))
+            .downcast_mut()
+            .expect("The topic is keyed by the type of messages")
+    }
+}
+
+struct Topic<M> {
+    subscribers: HashMap<Uuid, Subscriber<M>>,
+    retained: Option<M>,
+}
+
+struct Subscriber<M> {
+    recipient: Recipient<M>,
+    /// Unsubscribes an agent when it stops
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl<M: TopicMessage> Subscriber<M> {
+    /// Returns `false` if the subscriber can't receive messages anymore.
+    fn send(&self, id: &Uuid, message: M) -> bool {
+        let Err(err) = self.recipient.send(message) else {
+            return true;
+        };
+        // A full mailbox loses the message, but keeps the subscription
+        let full = err
+            .downcast_ref::<Undelivered<M>>()
+            .is_some_and(|undelivered| undelivered.reason() == UndeliveredReason::Full);
+        if !full {
+            log::debug!("Subscriber {id} has been removed: {err}");
+        }
+        full
+    }
+}
+
+impl<M> Drop for Subscriber<M> {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl<M> Default for Topic<M> {
+    fn default() -> Self {
+        Self {
+            subscribers: HashMap::new(),
+            retained: None,
+        }
+    }
+}
+
+impl<M: TopicMessage> Topic<M> {
+    fn deliver(&mut self, message: &M) {
+        self.subscribers
+            .retain(|id, subscriber| subscriber.send(id, message.clone()));
+    }
+}
+
+/// Methods of the broker's address.
+pub trait BrokerAddress {
+    /// Sends the message to all subscribers of the topic.
+    fn publish<M: TopicMessage>(&self, topic: &str, message: M) -> Result<()>;
+
+    /// Publishes the message and keeps it for subscribers that join later.
+    fn publish_retained<M: TopicMessage>(&self, topic: &str, message: M) -> Result<()>;
+
+    /// Subscribes the recipient to the topic.
+    ///
+    /// The subscription is removed once the recipient fails to receive a message.
+    fn subscribe<M: TopicMessage>(&self, topic: &str, recipient: Recipient<M>) -> Result<Uuid>;
+
+    /// Subscribes the agent to the topic until the agent stops.
+    fn subscribe_agent<A, M>(&self, topic: &str, address: &Address<A>) -> Result<Uuid>
+    where
+        A: OnEvent<M>,
+        M: TopicMessage;
+
+    fn unsubscribe<M: TopicMessage>(&self, topic: &str, id: Uuid) -> Result<()>;
+}
+
+impl BrokerAddress for Address<Broker> {
+    fn publish<M: TopicMessage>(&self, topic: &str, message: M) -> Result<()> {
+        let topic = topic.to_string();
+        self.send(Publish {
+            topic,
+            message,
+            retain: false,
+        })
+    }
+
+    fn publish_retained<M: TopicMessage>(&self, topic: &str, message: M) -> Result<()> {
+        let topic = topic.to_string();
+        self.send(Publish {
+            topic,
+            message,
+            retain: true,
+        })
+    }
+
+    fn subscribe<M: TopicMessage>(&self, topic: &str, recipient: Recipient<M>) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let topic = topic.to_string();
+        let subscriber = Subscriber {
+            recipient,
+            watcher: None,
+        };
+        self.send(Subscribe {
+            topic,
+            id,
+            subscriber,
+        })?;
+        Ok(id)
+    }
+
+    fn subscribe_agent<A, M>(&self, topic: &str, address: &Address<A>) -> Result<Uuid>
+    where
+        A: OnEvent<M>,
+        M: TopicMessage,
+    {
+        let mut subscriber = address.downgrade();
+        let id = subscriber.id();
+        let recipient = subscriber.recipient();
+        let broker = self.downgrade();
+        let unsubscribe = Unsubscribe::<M> {
+            topic: topic.to_string(),
+            id,
+            _type: PhantomData,
+        };
+        let watcher = crb_core::spawn(async move {
+            subscriber.join().await.ok();
+            if let Some(broker) = broker.upgrade() {
+                broker.send(unsubscribe).ok();
+            }
+        });
+        let subscriber = Subscriber {
+            recipient,
+            watcher: Some(watcher),
+        };
+        self.send(Subscribe {
+            topic: topic.to_string(),
+            id,
+            subscriber,
+        })?;
+        Ok(id)
+    }
+
+    fn unsubscribe<M: TopicMessage>(&self, topic: &str, id: Uuid) -> Result<()> {
+        self.send(Unsubscribe::<M> {
+            topic: topic.to_string(),
+            id,
+            _type: PhantomData,
+        })
+    }
+}
+
+struct Publish<M> {
+    topic: String,
+    message: M,
+    retain: bool,
+}
+
+#[async_trait]
+impl<M: TopicMessage> MessageFor<Broker> for Publish<M> {
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Broker,
+        _ctx: &mut AgentSession<Broker>,
+    ) -> Result<()> {
+        let topic = agent.topic::<M>(self.topic);
+        topic.deliver(&self.message);
+        if self.retain {
+            topic.retained = Some(self.message);
+        }
+        Ok(())
+    }
+}
+
+struct Subscribe<M> {
+    topic: String,
+    id: Uuid,
+    subscriber: Subscriber<M>,
+}
+
+#[async_trait]
+impl<M: TopicMessage> MessageFor<Broker> for Subscribe<M> {
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Broker,
+        _ctx: &mut AgentSession<Broker>,
+    ) -> Result<()> {
+        let topic = agent.topic::<M>(self.topic);
+        if let Some(retained) = topic.retained.clone() {
+            if !self.subscriber.send(&self.id, retained) {
+                return Ok(());
+            }
+        }
+        topic.subscribers.insert(self.id, self.subscriber);
+        Ok(())
+    }
+}
+
+struct Unsubscribe<M> {
+    topic: String,
+    id: Uuid,
+    _type: PhantomData<fn(M)>,
+}
+
+#[async_trait]
+impl<M: TopicMessage> MessageFor<Broker> for Unsubscribe<M> {
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Broker,
+        _ctx: &mut AgentSession<Broker>,
+    ) -> Result<()> {
+        let key = (TypeId::of::<M>(), self.topic);
+        if let Some(topic) = agent.topics.get_mut(&key) {
+            let topic: &mut Topic<M> = topic
+                .downcast_mut()
+                .expect("The topic is keyed by the type of messages");
+            topic.subscribers.remove(&self.id);
+            if topic.subscribers.is_empty() && topic.retained.is_none() {
+                agent.topics.remove(&key);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+//! Restart delays and intensity windows are compared with deadlines
+//! of timers, so they have to follow the same clock as timers do.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use crb_core::time::Instant;
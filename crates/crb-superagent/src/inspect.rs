@@ -0,0 +1,274 @@
+use crate::interaction::{Fetcher, Interaction, Request};
+use crate::supervisor::{ActivityId, Supervisor, SupervisorContext, SupervisorSession};
+use anyhow::{anyhow as err, Result};
+use async_trait::async_trait;
+use crb_agent::{Address, MessageFor, WeakAddress};
+use crb_core::time::{timeout, Duration};
+use crb_core::uuid::Uuid;
+use futures::future::join_all;
+use futures::Future;
+use std::any::type_name;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Write};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// How long a nested supervisor may take to report its children.
+const NESTED_TIMEOUT: Duration = Duration::from_secs(1);
+
+type Inspector = Arc<dyn Fn() -> Fetcher<TreeSnapshot> + Send + Sync>;
+
+/// Inspectors of supervisors by ids of their addresses
+/// to gather snapshots of nested supervisors.
+static INSPECTORS: Mutex<BTreeMap<Uuid, Inspector>> = Mutex::new(BTreeMap::new());
+
+fn lock_inspectors() -> MutexGuard<'static, BTreeMap<Uuid, Inspector>> {
+    INSPECTORS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Keeps a supervisor discoverable by its parent while it exists.
+pub(crate) struct InspectorGuard {
+    id: Uuid,
+}
+
+impl InspectorGuard {
+    pub(crate) fn register<S>(address: &Address<S>) -> Self
+    where
+        S: Supervisor,
+        S::Context: SupervisorContext<S>,
+    {
+        let id = address.id();
+        let address = address.downgrade();
+        let inspector: Inspector = Arc::new(move || inspect(&address));
+        lock_inspectors().insert(id, inspector);
+        Self { id }
+    }
+}
+
+impl Drop for InspectorGuard {
+    fn drop(&mut self) {
+        lock_inspectors().remove(&self.id);
+    }
+}
+
+fn inspect<S>(address: &WeakAddress<S>) -> Fetcher<TreeSnapshot>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    match address.upgrade() {
+        Some(address) => address.snapshot(),
+        None => Fetcher::spoiled(err!("The supervisor has finished")),
+    }
+}
+
+/// A state of a child in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildState {
+    /// Waits for previous groups to be ready.
+    Waiting,
+    /// Started, but not ready yet.
+    Starting,
+    Running,
+    /// Interrupted, but not finished yet.
+    Stopping,
+}
+
+impl Display for ChildState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            Self::Waiting => "waiting",
+            Self::Starting => "starting",
+            Self::Running => "running",
+            Self::Stopping => "stopping",
+        };
+        f.write_str(state)
+    }
+}
+
+/// A child of a supervisor with children of its own
+/// if it's a supervisor too.
+#[derive(Debug, Clone)]
+pub struct ChildInfo {
+    pub id: ActivityId,
+    pub group: String,
+    pub type_name: &'static str,
+    pub state: ChildState,
+    pub uptime: Duration,
+    pub children: Vec<ChildInfo>,
+}
+
+/// A snapshot of a supervision tree.
+#[derive(Debug, Clone)]
+pub struct TreeSnapshot {
+    pub supervisor: &'static str,
+    pub children: Vec<ChildInfo>,
+}
+
+impl TreeSnapshot {
+    /// Renders the snapshot as a JSON document.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        json.push_str("{\"supervisor\":");
+        write_json_str(&mut json, self.supervisor);
+        json.push_str(",\"children\":");
+        write_json_children(&mut json, &self.children);
+        json.push('}');
+        json
+    }
+}
+
+fn write_json_children(json: &mut String, children: &[ChildInfo]) {
+    json.push('[');
+    for (idx, child) in children.iter().enumerate() {
+        if idx > 0 {
+            json.push(',');
+        }
+        let id: usize = child.id.into();
+        write!(json, "{{\"id\":{id},\"group\":").ok();
+        write_json_str(json, &child.group);
+        json.push_str(",\"type\":");
+        write_json_str(json, child.type_name);
+        write!(
+            json,
+            ",\"state\":\"{}\",\"uptime_ms\":{},\"children\":",
+            child.state,
+            child.uptime.as_millis()
+        )
+        .ok();
+        write_json_children(json, &child.children);
+        json.push('}');
+    }
+    json.push(']');
+}
+
+fn write_json_str(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                write!(json, "\\u{:04x}", c as u32).ok();
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Renders the snapshot as an indented tree.
+impl Display for TreeSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.supervisor)?;
+        fmt_children(f, &self.children, 1)
+    }
+}
+
+fn fmt_children(f: &mut fmt::Formatter<'_>, children: &[ChildInfo], depth: usize) -> fmt::Result {
+    for child in children {
+        let id: usize = child.id.into();
+        writeln!(
+            f,
+            "{:indent$}#{id} {} [{}] {} {:?}",
+            "",
+            child.type_name,
+            child.group,
+            child.state,
+            child.uptime,
+            indent = depth * 2,
+        )?;
+        fmt_children(f, &child.children, depth + 1)?;
+    }
+    Ok(())
+}
+
+impl<S> SupervisorSession<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    /// Gathers a snapshot of children including children of nested supervisors.
+    pub fn snapshot(&self) -> impl Future<Output = TreeSnapshot> + Send + 'static {
+        let children: Vec<_> = self
+            .tracker
+            .activities()
+            .map(|(id, activity)| {
+                let nested = activity
+                    .agent_id
+                    .and_then(|id| lock_inspectors().get(&id).cloned())
+                    .map(|inspector| inspector());
+                let info = ChildInfo {
+                    id,
+                    group: format!("{:?}", activity.group),
+                    type_name: activity.type_name,
+                    state: activity.state(),
+                    uptime: activity.uptime(),
+                    children: Vec::new(),
+                };
+                (info, nested)
+            })
+            .collect();
+        async move {
+            let children = children.into_iter().map(|(mut info, nested)| async move {
+                if let Some(nested) = nested {
+                    // An unresponsive supervisor is shown without children
+                    if let Ok(Ok(snapshot)) = timeout(Some(NESTED_TIMEOUT), nested).await {
+                        info.children = snapshot.children;
+                    }
+                }
+                info
+            });
+            TreeSnapshot {
+                supervisor: type_name::<S>(),
+                children: join_all(children).await,
+            }
+        }
+    }
+}
+
+/// Inspects a running supervisor.
+pub trait SupervisorAddress {
+    /// Requests a snapshot of the supervision tree.
+    fn snapshot(&self) -> Fetcher<TreeSnapshot>;
+}
+
+impl<S> SupervisorAddress for Address<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    fn snapshot(&self) -> Fetcher<TreeSnapshot> {
+        let (interaction, fetcher) = Interaction::new_pair(Inspect);
+        let res = self.send(InspectTree { interaction });
+        fetcher.grasp(res)
+    }
+}
+
+struct Inspect;
+
+impl Request for Inspect {
+    type Response = TreeSnapshot;
+}
+
+struct InspectTree {
+    interaction: Interaction<Inspect>,
+}
+
+#[async_trait]
+impl<S> MessageFor<S> for InspectTree
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    async fn handle(self: Box<Self>, _agent: &mut S, ctx: &mut S::Context) -> Result<()> {
+        let snapshot = ctx.session().snapshot();
+        // Nested supervisors are not awaited in the handler
+        crb_core::spawn(async move {
+            self.interaction.responder.send(snapshot.await).ok();
+        });
+        Ok(())
+    }
+}
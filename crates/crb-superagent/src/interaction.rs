@@ -1,12 +1,13 @@
 use anyhow::{anyhow, Error, Result};
 use async_trait::async_trait;
-use crb_agent::{Address, Agent, MessageFor};
+use crb_agent::{Address, Agent, Headers, MessageFor};
 use crb_core::Tag;
 use futures::{
     channel::oneshot::{self, Canceled},
     task::{Context as FutContext, Poll},
     Future,
 };
+use std::any::type_name;
 use std::pin::Pin;
 use thiserror::Error;
 
@@ -48,7 +49,7 @@ impl<R: Request> Interaction<R> {
         let (tx, rx) = oneshot::channel();
         let responder = Responder { tx };
         let interaction = Interaction { request, responder };
-        let fetcher = Fetcher { rx };
+        let fetcher = Fetcher { rx, headers: None };
         (interaction, fetcher)
     }
 }
@@ -62,6 +63,10 @@ where
     async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut A::Context) -> Result<()> {
         agent.handle(*self, ctx).await
     }
+
+    fn message_type(&self) -> &'static str {
+        type_name::<R>()
+    }
 }
 
 #[async_trait]
@@ -79,20 +84,29 @@ pub trait OnRequest<R: Request>: Agent {
 #[must_use]
 pub struct Fetcher<OUT> {
     rx: oneshot::Receiver<Result<OUT>>,
+    /// Headers of the request that are forwarded with the response.
+    headers: Option<Headers>,
 }
 
 impl<OUT> Fetcher<OUT> {
     pub fn grasp(self, result: Result<()>) -> Self {
         match result {
             Ok(_) => self,
-            Err(err) => Self::spoiled(err),
+            Err(err) => Fetcher {
+                headers: self.headers,
+                ..Self::spoiled(err)
+            },
         }
     }
 
     pub fn spoiled(err: Error) -> Fetcher<OUT> {
         let (tx, rx) = oneshot::channel();
         tx.send(Err(err)).ok();
-        Fetcher { rx }
+        Fetcher { rx, headers: None }
+    }
+
+    pub fn headers(&self) -> Option<&Headers> {
+        self.headers.as_ref()
     }
 
     pub fn forward_to<A, T>(self, address: Address<A>, tag: T)
@@ -102,8 +116,14 @@ impl<OUT> Fetcher<OUT> {
         T: Tag,
     {
         crb_core::spawn(async move {
+            let headers = self.headers.as_ref().map(Headers::forward);
             let response = self.await;
-            if let Err(err) = address.send(Response { response, tag }) {
+            let msg = Response { response, tag };
+            let res = match headers {
+                Some(headers) => address.send_with(msg, headers),
+                None => address.send(msg),
+            };
+            if let Err(err) = res {
                 log::error!("Can't send a reponse: {err}");
             }
         });
@@ -123,6 +143,8 @@ impl<OUT> Future for Fetcher<OUT> {
 
 pub trait AddressExt<R: Request> {
     fn interact(&self, request: R) -> Fetcher<R::Response>;
+
+    fn interact_with(&self, request: R, headers: Headers) -> Fetcher<R::Response>;
 }
 
 impl<A, R> AddressExt<R> for Address<A>
@@ -131,8 +153,17 @@ where
     R: Request,
 {
     fn interact(&self, request: R) -> Fetcher<R::Response> {
-        let (msg, fetcher) = Interaction::new_pair(request);
+        let (msg, mut fetcher) = Interaction::new_pair(request);
         let res = self.send(msg);
+        // Responses continue the flow of the handler
+        fetcher.headers = Headers::inherited();
+        fetcher.grasp(res)
+    }
+
+    fn interact_with(&self, request: R, headers: Headers) -> Fetcher<R::Response> {
+        let (msg, mut fetcher) = Interaction::new_pair(request);
+        let res = self.send_with(msg, headers.clone());
+        fetcher.headers = Some(headers);
         fetcher.grasp(res)
     }
 }
@@ -1,12 +1,23 @@
+pub mod broker;
+mod clock;
+pub mod inspect;
 pub mod interaction;
 pub mod interval;
 pub mod molting;
+pub mod pool;
+pub mod registry;
+pub mod restart;
 pub mod subagent;
 pub mod supervisor;
 pub mod timeout;
 
+pub use broker::*;
+pub use inspect::*;
 pub use interaction::*;
 pub use interval::*;
 pub use molting::*;
+pub use pool::*;
+pub use registry::*;
+pub use restart::*;
 pub use supervisor::*;
 pub use timeout::*;
@@ -0,0 +1,417 @@
+use crate::clock::Instant;
+use crate::interaction::{Fetcher, Interaction, OnRequest, Request};
+use crate::restart::{Backoff, Intensity};
+use crate::supervisor::{ActivityId, Relation, Supervisor, SupervisorSession};
+use crate::timeout::Timeout;
+use anyhow::{anyhow as err, Result};
+use async_trait::async_trait;
+use crb_agent::message::event::Event;
+use crb_agent::{Address, Agent, MessageFor, Next, OnEvent, Standalone};
+use crb_core::time::Duration;
+use crb_runtime::{Context, ManagedContext};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// A strategy of picking a worker for a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Routing {
+    #[default]
+    RoundRobin,
+    /// Picks the worker with the shortest mailbox queue.
+    LeastQueueLen,
+    /// Picks the same worker for the same key. Resizing moves only
+    /// keys of added or removed workers, and keys of a worker that
+    /// waits for a restart go to the next worker meanwhile.
+    /// Messages without a key are routed by round-robin.
+    ConsistentHash,
+}
+
+pub type WorkerFactory<A> = Arc<dyn Fn() -> (A, <A as Agent>::Context) + Send + Sync>;
+
+struct Worker<A: Agent> {
+    state: WorkerState<A>,
+    started: Instant,
+    attempt: u32,
+}
+
+enum WorkerState<A: Agent> {
+    Running {
+        id: ActivityId,
+        address: Address<A>,
+    },
+    /// Waits for a delayed restart
+    Restarting {
+        _timer: Timeout,
+    },
+}
+
+impl<A: Agent> Worker<A> {
+    fn address(&self) -> Option<&Address<A>> {
+        match &self.state {
+            WorkerState::Running { address, .. } => Some(address),
+            WorkerState::Restarting { .. } => None,
+        }
+    }
+
+    fn is(&self, activity: ActivityId) -> bool {
+        matches!(self.state, WorkerState::Running { id, .. } if id == activity)
+    }
+}
+
+/// A supervisor that spawns identical workers and routes messages to them.
+///
+/// Workers that stop are restarted while the pool is alive.
+/// The pool fails if workers restart more often than its intensity allows.
+pub struct Pool<A: Agent> {
+    factory: WorkerFactory<A>,
+    size: usize,
+    routing: Routing,
+    intensity: Intensity,
+    backoff: Option<Backoff>,
+    restarts: VecDeque<Instant>,
+    next: usize,
+    workers: Vec<Worker<A>>,
+}
+
+impl<A> Pool<A>
+where
+    A: Agent,
+    A::Context: Default,
+{
+    pub fn new<F>(size: usize, factory: F) -> Self
+    where
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        Self::with_context(size, move || (factory(), A::Context::default()))
+    }
+}
+
+impl<A: Agent> Pool<A> {
+    /// Creates a pool of workers that are started with their own contexts.
+    pub fn with_context<F>(size: usize, factory: F) -> Self
+    where
+        F: Fn() -> (A, A::Context) + Send + Sync + 'static,
+    {
+        Self {
+            factory: Arc::new(factory),
+            size,
+            routing: Routing::default(),
+            intensity: Intensity::default(),
+            backoff: None,
+            restarts: VecDeque::new(),
+            next: 0,
+            workers: Vec::new(),
+        }
+    }
+
+    pub fn routing(mut self, routing: Routing) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Limits restarts of workers. The pool fails when it's exceeded.
+    pub fn intensity(mut self, intensity: Intensity) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Delays restarts of workers.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    fn spawn_worker(&mut self, ctx: &mut SupervisorSession<Self>) -> WorkerState<A> {
+        let (agent, context) = (self.factory)();
+        let (address, rel) = ctx.spawn_agent_with_context(agent, context, ());
+        WorkerState::Running {
+            id: rel.id,
+            address,
+        }
+    }
+
+    fn resize(&mut self, size: usize, ctx: &mut SupervisorSession<Self>) {
+        self.size = size;
+        while self.workers.len() < size {
+            let worker = Worker {
+                state: self.spawn_worker(ctx),
+                started: Instant::now(),
+                attempt: 0,
+            };
+            self.workers.push(worker);
+        }
+        for worker in self.workers.drain(size..) {
+            if let Some(address) = worker.address() {
+                address.interrupt().ok();
+            }
+        }
+    }
+
+    /// Restarts the worker in the slot `idx` immediately or after a delay.
+    fn restart(&mut self, idx: usize, ctx: &mut SupervisorSession<Self>) -> Result<()> {
+        self.intensity.check(&mut self.restarts)?;
+        let worker = &mut self.workers[idx];
+        let delay = match self.backoff {
+            Some(backoff) => backoff.next_delay(worker.started, &mut worker.attempt),
+            None => Duration::ZERO,
+        };
+        if delay.is_zero() {
+            self.respawn(idx, ctx);
+        } else {
+            let address = ctx.address().downgrade();
+            let timer = Timeout::weak_message(address, delay, Respawn { idx });
+            self.workers[idx].state = WorkerState::Restarting { _timer: timer };
+        }
+        Ok(())
+    }
+
+    fn respawn(&mut self, idx: usize, ctx: &mut SupervisorSession<Self>) {
+        let state = self.spawn_worker(ctx);
+        let worker = &mut self.workers[idx];
+        worker.state = state;
+        worker.started = Instant::now();
+    }
+
+    fn route(&mut self, key: Option<u64>) -> Result<&Address<A>> {
+        let len = self.workers.len();
+        if len == 0 {
+            return Err(err!("The pool has no workers"));
+        }
+        let idx = match (self.routing, key) {
+            (Routing::ConsistentHash, Some(key)) => jump_hash(key, len),
+            (Routing::LeastQueueLen, _) => self
+                .workers
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, worker)| Some((idx, worker.address()?)))
+                .min_by_key(|(_, address)| address.queue_len())
+                .map(|(idx, _)| idx)
+                .unwrap_or_default(),
+            _ => {
+                self.next = self.next.wrapping_add(1);
+                self.next % len
+            }
+        };
+        // Skips workers that wait for a restart
+        (0..len)
+            .map(|offset| (idx + offset) % len)
+            .find_map(|idx| self.workers[idx].address())
+            .ok_or_else(|| err!("The pool has no workers"))
+    }
+
+    fn forward<M>(&mut self, key: Option<u64>, msg: M, ctx: &SupervisorSession<Self>) -> Result<()>
+    where
+        M: MessageFor<A>,
+    {
+        let headers = ctx.headers().cloned();
+        let worker = self.route(key)?;
+        match headers {
+            Some(headers) => worker.send_with(msg, headers),
+            None => worker.send(msg),
+        }
+    }
+}
+
+/// Jump consistent hash by Lamping and Veach.
+fn jump_hash(mut key: u64, buckets: usize) -> usize {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < buckets as i64 {
+        b = j;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as usize
+}
+
+fn hash_key(key: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<A: Agent> Standalone for Pool<A> {}
+
+impl<A> Agent for Pool<A>
+where
+    A: Agent,
+{
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        self.resize(self.size, ctx);
+        Next::events()
+    }
+
+    fn interrupt(&mut self, ctx: &mut Self::Context) {
+        // Idle workers are waiting for messages and have to be woken up
+        for address in self.workers.iter().filter_map(Worker::address) {
+            address.interrupt().ok();
+        }
+        ctx.shutdown();
+    }
+}
+
+impl<A> Supervisor for Pool<A>
+where
+    A: Agent,
+{
+    type GroupBy = ();
+
+    fn intensity(&self) -> Intensity {
+        self.intensity
+    }
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Self::Context) {
+        let position = self.workers.iter().position(|worker| worker.is(rel.id));
+        if let Some(idx) = position {
+            if ctx.tracker.is_terminating() {
+                self.workers.remove(idx);
+            } else if let Err(err) = self.restart(idx, ctx) {
+                ctx.escalate(err);
+            }
+        }
+    }
+}
+
+struct Respawn {
+    idx: usize,
+}
+
+#[async_trait]
+impl<A> MessageFor<Pool<A>> for Respawn
+where
+    A: Agent,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<A>,
+        ctx: &mut SupervisorSession<Pool<A>>,
+    ) -> Result<()> {
+        // The slot could be removed or reused by resizing
+        let restarting = agent
+            .workers
+            .get(self.idx)
+            .is_some_and(|worker| matches!(worker.state, WorkerState::Restarting { .. }));
+        if restarting && !ctx.tracker.is_terminating() {
+            agent.respawn(self.idx, ctx);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<A, E> OnEvent<E> for Pool<A>
+where
+    A: OnEvent<E>,
+    E: Send + 'static,
+{
+    async fn handle(&mut self, event: E, ctx: &mut Self::Context) -> Result<()> {
+        self.forward(None, Event::new(event), ctx)
+    }
+}
+
+#[async_trait]
+impl<A, R> OnRequest<R> for Pool<A>
+where
+    A: OnRequest<R>,
+    R: Request,
+{
+    async fn handle(&mut self, msg: Interaction<R>, ctx: &mut Self::Context) -> Result<()> {
+        self.forward(None, msg, ctx)
+    }
+}
+
+/// Methods of the pool's address.
+pub trait PoolAddress<A: Agent> {
+    /// Sends the event to the worker picked by the `key`.
+    fn event_keyed<E>(&self, key: impl Hash, event: E) -> Result<()>
+    where
+        A: OnEvent<E>,
+        E: Send + 'static;
+
+    /// Sends the request to the worker picked by the `key`.
+    fn interact_keyed<R>(&self, key: impl Hash, request: R) -> Fetcher<R::Response>
+    where
+        A: OnRequest<R>,
+        R: Request;
+
+    /// Changes the number of workers.
+    fn resize(&self, size: usize) -> Result<()>;
+}
+
+impl<A> PoolAddress<A> for Address<Pool<A>>
+where
+    A: Agent,
+{
+    fn event_keyed<E>(&self, key: impl Hash, event: E) -> Result<()>
+    where
+        A: OnEvent<E>,
+        E: Send + 'static,
+    {
+        let key = hash_key(key);
+        let msg = Event::new(event);
+        self.send(Keyed { key, msg })
+    }
+
+    fn interact_keyed<R>(&self, key: impl Hash, request: R) -> Fetcher<R::Response>
+    where
+        A: OnRequest<R>,
+        R: Request,
+    {
+        let key = hash_key(key);
+        let (msg, fetcher) = Interaction::new_pair(request);
+        let res = self.send(Keyed { key, msg });
+        fetcher.grasp(res)
+    }
+
+    fn resize(&self, size: usize) -> Result<()> {
+        self.send(Resize { size })
+    }
+}
+
+struct Keyed<M> {
+    key: u64,
+    msg: M,
+}
+
+#[async_trait]
+impl<A, M> MessageFor<Pool<A>> for Keyed<M>
+where
+    A: Agent,
+    M: MessageFor<A>,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<A>,
+        ctx: &mut SupervisorSession<Pool<A>>,
+    ) -> Result<()> {
+        agent.forward(Some(self.key), self.msg, ctx)
+    }
+
+    fn message_type(&self) -> &'static str {
+        self.msg.message_type()
+    }
+}
+
+struct Resize {
+    size: usize,
+}
+
+#[async_trait]
+impl<A> MessageFor<Pool<A>> for Resize
+where
+    A: Agent,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<A>,
+        ctx: &mut SupervisorSession<Pool<A>>,
+    ) -> Result<()> {
+        agent.resize(self.size, ctx);
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow as err, Result};
+use crb_agent::{Address, Agent, OnEvent, WeakAddress};
+use crb_core::uuid::Uuid;
+use crb_send::Recipient;
+use std::any::{type_name, Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+static ENTRIES: Mutex<BTreeMap<Key, Entry>> = Mutex::new(BTreeMap::new());
+
+fn lock_entries() -> MutexGuard<'static, BTreeMap<Key, Entry>> {
+    ENTRIES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+enum Key {
+    Name(String),
+    Type(TypeId),
+}
+
+struct Entry {
+    id: Uuid,
+    address: Box<dyn Any + Send>,
+    recipients: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+/// A process-wide registry of agents.
+///
+/// The registry keeps weak addresses only. Entries are removed
+/// when their agents finish.
+pub struct Registry;
+
+impl Registry {
+    /// Registers the agent under the `name`.
+    pub fn register<A: Agent>(name: &str, address: &Address<A>) -> Result<Registration<A>> {
+        let key = Key::Name(name.to_string());
+        Self::insert(key, address).map_err(|_| err!("The name {name} is already registered"))
+    }
+
+    /// Registers the agent as a singleton of its type.
+    pub fn register_singleton<A: Agent>(address: &Address<A>) -> Result<Registration<A>> {
+        let key = Key::Type(TypeId::of::<A>());
+        Self::insert(key, address)
+            .map_err(|_| err!("The singleton {} is already registered", type_name::<A>()))
+    }
+
+    /// Returns the address of the agent registered under the `name`.
+    pub fn lookup<A: Agent>(name: &str) -> Option<Address<A>> {
+        Self::get(&Key::Name(name.to_string()))
+    }
+
+    /// Returns the address of the singleton of the type `A`.
+    pub fn singleton<A: Agent>() -> Option<Address<A>> {
+        Self::get(&Key::Type(TypeId::of::<A>()))
+    }
+
+    /// Returns a recipient of events that was exposed by the agent
+    /// registered under the `name`.
+    pub fn recipient<E: Send + 'static>(name: &str) -> Option<Recipient<E>> {
+        Self::get_recipient(&Key::Name(name.to_string()))
+    }
+
+    /// Returns a recipient of events that was exposed by the singleton of the type `A`.
+    pub fn singleton_recipient<A: Agent, E: Send + 'static>() -> Option<Recipient<E>> {
+        Self::get_recipient(&Key::Type(TypeId::of::<A>()))
+    }
+
+    /// Names of registered agents.
+    pub fn names() -> Vec<String> {
+        lock_entries()
+            .keys()
+            .filter_map(|key| match key {
+                Key::Name(name) => Some(name.clone()),
+                Key::Type(_) => None,
+            })
+            .collect()
+    }
+
+    /// Removes the agent registered under the `name`.
+    pub fn unregister(name: &str) {
+        lock_entries().remove(&Key::Name(name.to_string()));
+    }
+
+    /// Removes the singleton of the type `A`.
+    pub fn unregister_singleton<A: Agent>() {
+        lock_entries().remove(&Key::Type(TypeId::of::<A>()));
+    }
+
+    fn insert<A: Agent>(key: Key, address: &Address<A>) -> Result<Registration<A>, Key> {
+        let address = address.downgrade();
+        let id = address.id();
+        {
+            let mut entries = lock_entries();
+            if entries.contains_key(&key) {
+                return Err(key);
+            }
+            let entry = Entry {
+                id,
+                address: Box::new(address.clone()),
+                recipients: HashMap::new(),
+            };
+            entries.insert(key.clone(), entry);
+        }
+        // Called without the lock, because the agent could be finished already
+        let finished = key.clone();
+        address.on_finished(move || {
+            let mut entries = lock_entries();
+            if entries.get(&finished).is_some_and(|entry| entry.id == id) {
+                entries.remove(&finished);
+            }
+        });
+        Ok(Registration { key, address })
+    }
+
+    fn get<A: Agent>(key: &Key) -> Option<Address<A>> {
+        lock_entries()
+            .get(key)?
+            .address
+            .downcast_ref::<WeakAddress<A>>()?
+            .upgrade()
+    }
+
+    fn get_recipient<E: Send + 'static>(key: &Key) -> Option<Recipient<E>> {
+        lock_entries()
+            .get(key)?
+            .recipients
+            .get(&TypeId::of::<E>())?
+            .downcast_ref::<Recipient<E>>()
+            .cloned()
+    }
+}
+
+/// A handle to expose recipients of a registered agent.
+pub struct Registration<A: Agent> {
+    key: Key,
+    address: WeakAddress<A>,
+}
+
+impl<A: Agent> Registration<A> {
+    /// Makes events of the type `E` available with `Registry::recipient`
+    /// or `Registry::singleton_recipient`.
+    pub fn expose<E>(self) -> Self
+    where
+        A: OnEvent<E>,
+        E: Send + 'static,
+    {
+        let recipient = self.address.recipient();
+        if let Some(entry) = lock_entries().get_mut(&self.key) {
+            if entry.id == self.address.id() {
+                entry
+                    .recipients
+                    .insert(TypeId::of::<E>(), Box::new(recipient));
+            }
+        }
+        self
+    }
+}
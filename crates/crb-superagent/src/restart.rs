@@ -0,0 +1,381 @@
+use crate::clock::Instant;
+use crate::supervisor::{ActivityId, Relation, Supervisor, SupervisorContext, SupervisorSession};
+use crate::timeout::Timeout;
+use anyhow::{anyhow as err, Result};
+use async_trait::async_trait;
+use crb_agent::{Agent, MessageFor};
+use crb_core::time::Duration;
+use crb_core::uuid::Uuid;
+use crb_runtime::Context;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// When a child has to be restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Restart {
+    /// Always restarted.
+    #[default]
+    Permanent,
+    /// Restarted only if it failed.
+    Transient,
+    /// Never restarted.
+    Temporary,
+}
+
+impl Restart {
+    fn is_required(&self, failed: bool) -> bool {
+        match self {
+            Self::Permanent => true,
+            Self::Transient => failed,
+            Self::Temporary => false,
+        }
+    }
+}
+
+/// Which children are restarted when one of them stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Only the stopped child.
+    #[default]
+    OneForOne,
+    /// All children.
+    OneForAll,
+    /// The stopped child and children started after it.
+    RestForOne,
+}
+
+/// The maximal number of restarts within a period.
+/// The supervisor fails when it's exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intensity {
+    pub max_restarts: usize,
+    pub within: Duration,
+}
+
+impl Intensity {
+    /// Records a restart in the `restarts` log or fails if it's exceeded.
+    pub(crate) fn check(&self, restarts: &mut VecDeque<Instant>) -> Result<()> {
+        let now = Instant::now();
+        while let Some(time) = restarts.front() {
+            if now.duration_since(*time) > self.within {
+                restarts.pop_front();
+            } else {
+                break;
+            }
+        }
+        if restarts.len() >= self.max_restarts {
+            return Err(err!(
+                "The restart intensity of {} restarts within {:?} is exceeded",
+                self.max_restarts,
+                self.within
+            ));
+        }
+        restarts.push_back(now);
+        Ok(())
+    }
+}
+
+impl Default for Intensity {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            within: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Delays of restarts that grow exponentially for a child
+/// that keeps failing and are reset after a period of stability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: f64,
+    /// A random deviation of a delay as a fraction of it.
+    pub jitter: f64,
+    /// Resets delays if the child has worked longer than this.
+    pub reset_after: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            factor: 2.0,
+            jitter: 0.1,
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// A delay of the next restart of a child started at `started`.
+    pub(crate) fn next_delay(&self, started: Instant, attempt: &mut u32) -> Duration {
+        if started.elapsed() >= self.reset_after {
+            *attempt = 0;
+        }
+        let delay = self.delay(*attempt);
+        *attempt = attempt.saturating_add(1);
+        delay
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.initial.as_secs_f64() * self.factor.powi(attempt as i32);
+        let delay = delay.min(self.max.as_secs_f64());
+        // Random bits of a uuid are enough for a jitter
+        let random = (Uuid::new_v4().as_u128() >> 64) as u64 as f64 / u64::MAX as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0) * (random * 2.0 - 1.0);
+        Duration::from_secs_f64(delay * (1.0 + jitter))
+    }
+}
+
+type SpawnChild<S> = Arc<dyn Fn(&mut SupervisorSession<S>) -> RunningChild<S> + Send + Sync>;
+
+/// A specification to start and restart a child.
+pub struct ChildSpec<S: Supervisor> {
+    restart: Restart,
+    backoff: Option<Backoff>,
+    spawn: SpawnChild<S>,
+}
+
+impl<S> ChildSpec<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    pub fn new<A, F>(group: S::GroupBy, factory: F) -> Self
+    where
+        A: Agent,
+        A::Context: Default,
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        Self::with_context(group, move || (factory(), A::Context::default()))
+    }
+
+    /// Creates a spec for agents that are started with their own contexts.
+    pub fn with_context<A, F>(group: S::GroupBy, factory: F) -> Self
+    where
+        A: Agent,
+        F: Fn() -> (A, A::Context) + Send + Sync + 'static,
+    {
+        let spawn = move |session: &mut SupervisorSession<S>| {
+            let (agent, context) = factory();
+            let (address, rel) = session.spawn_agent_with_context(agent, context, group.clone());
+            let address = address.downgrade();
+            RunningChild {
+                rel,
+                interrupt: Box::new(move || {
+                    if let Some(address) = address.upgrade() {
+                        address.interrupt().ok();
+                    }
+                }),
+            }
+        };
+        Self {
+            restart: Restart::default(),
+            backoff: None,
+            spawn: Arc::new(spawn),
+        }
+    }
+
+    pub fn restart(mut self, restart: Restart) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Delays restarts of the child.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+}
+
+struct RunningChild<S: Supervisor> {
+    rel: Relation<S>,
+    interrupt: Box<dyn Fn() + Send>,
+}
+
+struct Child<S: Supervisor> {
+    spec: ChildSpec<S>,
+    running: Option<RunningChild<S>>,
+    /// Waits for a restart by the strategy
+    pending: bool,
+    /// When a pending child is restarted
+    deadline: Instant,
+    started: Instant,
+    attempt: u32,
+}
+
+impl<S: Supervisor> Child<S> {
+    fn next_delay(&mut self) -> Duration {
+        match self.spec.backoff {
+            Some(backoff) => backoff.next_delay(self.started, &mut self.attempt),
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// Children that are started by specs.
+///
+/// Every failed child waits for its own delay. Siblings stopped
+/// by the strategy are restarted together with the failed child.
+pub struct Children<S: Supervisor> {
+    children: Vec<Child<S>>,
+    restarts: VecDeque<Instant>,
+    /// Fires at the earliest deadline of pending children
+    timer: Option<Timeout>,
+}
+
+impl<S: Supervisor> Default for Children<S> {
+    fn default() -> Self {
+        Self {
+            children: Vec::new(),
+            restarts: VecDeque::new(),
+            timer: None,
+        }
+    }
+}
+
+impl<S: Supervisor> Children<S> {
+    fn position(&self, id: ActivityId) -> Option<usize> {
+        self.children.iter().position(|child| {
+            child
+                .running
+                .as_ref()
+                .is_some_and(|running| running.rel.id == id)
+        })
+    }
+}
+
+impl<S> SupervisorSession<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    /// Starts a child that is restarted according to its spec
+    /// and the strategy of the supervisor.
+    pub fn spawn_child(&mut self, spec: ChildSpec<S>) -> Relation<S> {
+        let running = (spec.spawn)(self);
+        let rel = running.rel.clone();
+        let child = Child {
+            spec,
+            running: Some(running),
+            pending: false,
+            deadline: Instant::now(),
+            started: Instant::now(),
+            attempt: 0,
+        };
+        self.children.children.push(child);
+        rel
+    }
+
+    /// Restarts children when the child with the relation is detached.
+    pub(crate) fn restart_children(
+        &mut self,
+        rel: &Relation<S>,
+        failed: bool,
+        strategy: Strategy,
+        intensity: Intensity,
+    ) -> Result<()> {
+        let Some(idx) = self.children.position(rel.id) else {
+            return Ok(());
+        };
+        let child = &mut self.children.children[idx];
+        child.running = None;
+        let restart = child.spec.restart;
+        let pending = child.pending;
+        if self.tracker.is_terminating() {
+            self.children.children.remove(idx);
+            return Ok(());
+        }
+        if !pending {
+            // The child has stopped by itself
+            if !restart.is_required(failed) {
+                self.children.children.remove(idx);
+                return Ok(());
+            }
+            intensity.check(&mut self.children.restarts)?;
+            let child = &mut self.children.children[idx];
+            child.pending = true;
+            let deadline = Instant::now() + child.next_delay();
+            child.deadline = deadline;
+            let affected = match strategy {
+                Strategy::OneForOne => 0..0,
+                Strategy::OneForAll => 0..self.children.children.len(),
+                Strategy::RestForOne => idx..self.children.children.len(),
+            };
+            for sibling in &mut self.children.children[affected] {
+                if let Some(running) = sibling.running.as_ref() {
+                    sibling.pending = true;
+                    sibling.deadline = deadline;
+                    self.tracker.terminate_activity(running.rel.id);
+                    (running.interrupt)();
+                }
+            }
+        }
+        self.restart_pending();
+        Ok(())
+    }
+
+    /// Restarts pending children whose deadlines have passed
+    /// and schedules a restart of the remaining ones.
+    fn restart_pending(&mut self) {
+        self.children.timer = None;
+        let waiting = self
+            .children
+            .children
+            .iter()
+            .any(|child| child.pending && child.running.is_some());
+        if waiting {
+            // Restarts are scheduled when stopped siblings detach
+            return;
+        }
+        let now = Instant::now();
+        let is_due = |child: &Child<S>| child.pending && child.deadline <= now;
+        // Temporary children are not restarted by siblings
+        self.children
+            .children
+            .retain(|child| !is_due(child) || child.spec.restart != Restart::Temporary);
+        for idx in 0..self.children.children.len() {
+            if is_due(&self.children.children[idx]) {
+                let spawn = self.children.children[idx].spec.spawn.clone();
+                let running = spawn(self);
+                let child = &mut self.children.children[idx];
+                child.running = Some(running);
+                child.pending = false;
+                child.started = Instant::now();
+            }
+        }
+        let next = self
+            .children
+            .children
+            .iter()
+            .filter(|child| child.pending)
+            .map(|child| child.deadline)
+            .min();
+        if let Some(deadline) = next {
+            let address = self.address().downgrade();
+            let delay = deadline.duration_since(now);
+            let timer = Timeout::weak_message(address, delay, RestartPending);
+            self.children.timer = Some(timer);
+        }
+    }
+}
+
+struct RestartPending;
+
+#[async_trait]
+impl<S> MessageFor<S> for RestartPending
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    async fn handle(self: Box<Self>, _agent: &mut S, ctx: &mut S::Context) -> Result<()> {
+        let session = ctx.session();
+        if !session.tracker.is_terminating() {
+            session.restart_pending();
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,25 @@
-use anyhow::Error;
+use crate::clock::Instant;
+use crate::inspect::{ChildState, InspectorGuard};
+use crate::restart::{Children, Intensity, Strategy};
+use anyhow::{anyhow as err, Error, Result};
 use async_trait::async_trait;
-use crb_agent::{Address, Agent, AgentContext, AgentSession, MessageFor, RunAgent};
-use crb_runtime::{Context, InteractiveRuntime, Interruptor, ManagedContext, Runtime};
+use crb_agent::address::AgentStatus;
+use crb_agent::{
+    Address, Agent, AgentContext, AgentSession, MessageFor, Next, RunAgent, WeakAddress,
+};
+use crb_core::oneshot;
+use crb_core::time::{sleep, Duration};
+use crb_core::uuid::Uuid;
+use crb_core::JoinHandle;
+use crb_runtime::{Context, InteractiveRuntime, JobHandle, ManagedContext, Runtime};
+use crb_send::{Recipient, Sender};
 use derive_more::{Deref, DerefMut, From, Into};
-use std::collections::{BTreeMap, HashSet};
-use std::fmt::Debug;
+use futures::future::BoxFuture;
+use std::any::{type_name, Any, TypeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Debug};
 use std::hash::Hash;
+use std::sync::Arc;
 use typed_slab::TypedSlab;
 
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, From, Into)]
@@ -14,7 +28,39 @@ pub struct ActivityId(usize);
 pub trait Supervisor: Agent {
     type GroupBy: Debug + Ord + Clone + Sync + Send + Eq + Hash;
 
+    /// Starts groups one after another in the order of `GroupBy`.
+    /// Children of a group are started when all members of previous groups
+    /// are ready, so previous groups have to be spawned first.
+    const ORDERED_STARTUP: bool = false;
+
+    /// How long members of the group have to finish after an interruption
+    /// before they are aborted. Waits indefinitely if not set.
+    fn grace_period(_group: &Self::GroupBy) -> Option<Duration> {
+        None
+    }
+
+    /// Which children started by specs are restarted when one of them stops.
+    fn strategy(&self) -> Strategy {
+        Strategy::default()
+    }
+
+    /// The supervisor fails if children are restarted more often.
+    fn intensity(&self) -> Intensity {
+        Intensity::default()
+    }
+
     fn finished(&mut self, _rel: &Relation<Self>, _ctx: &mut Self::Context) {}
+
+    /// Called when a child detaches with the status, the error
+    /// and the output of the child. Calls `finished` by default.
+    fn finished_with_outcome(
+        &mut self,
+        rel: &Relation<Self>,
+        _outcome: ChildOutcome,
+        ctx: &mut Self::Context,
+    ) {
+        self.finished(rel, ctx);
+    }
 }
 
 pub trait SupervisorContext<S: Supervisor> {
@@ -27,6 +73,9 @@ pub struct SupervisorSession<S: Supervisor> {
     #[deref_mut]
     pub session: AgentSession<S>,
     pub tracker: Tracker<S>,
+    pub children: Children<S>,
+    inspector: Option<InspectorGuard>,
+    escalation: Option<Error>,
 }
 
 impl<S: Supervisor> Default for SupervisorSession<S> {
@@ -34,6 +83,9 @@ impl<S: Supervisor> Default for SupervisorSession<S> {
         Self {
             session: AgentSession::default(),
             tracker: Tracker::new(),
+            children: Children::default(),
+            inspector: None,
+            escalation: None,
         }
     }
 }
@@ -59,9 +111,7 @@ impl<S: Supervisor> ManagedContext for SupervisorSession<S> {
 
     fn shutdown(&mut self) {
         self.tracker.terminate_all();
-        if self.tracker.is_terminated() {
-            self.session.shutdown();
-        }
+        self.finish_if_terminated();
     }
 
     fn stop(&mut self) {
@@ -69,6 +119,32 @@ impl<S: Supervisor> ManagedContext for SupervisorSession<S> {
     }
 }
 
+impl<S: Supervisor> SupervisorSession<S> {
+    /// Returns the address of the child spawned with the `key`.
+    pub fn child<A: Agent>(&self, key: &str) -> Option<Address<A>> {
+        self.tracker.child(key)
+    }
+
+    /// Terminates all children and fails the supervisor with the `reason`
+    /// after that, so its parent receives it as a failure of a child.
+    pub fn escalate(&mut self, reason: impl Into<Error>) {
+        if self.escalation.is_none() {
+            self.escalation = Some(reason.into());
+        }
+        self.tracker.terminate_all();
+        self.finish_if_terminated();
+    }
+
+    fn finish_if_terminated(&mut self) {
+        if self.tracker.is_terminated() {
+            match self.escalation.take() {
+                Some(reason) => self.session.do_next(Next::fail(reason)),
+                None => self.session.shutdown(),
+            }
+        }
+    }
+}
+
 impl<S: Supervisor> AgentContext<S> for SupervisorSession<S> {
     fn session(&mut self) -> &mut AgentSession<S> {
         &mut self.session
@@ -84,7 +160,11 @@ impl<S: Supervisor> SupervisorContext<S> for SupervisorSession<S> {
 #[derive(Debug, Default)]
 struct Group {
     interrupted: bool,
+    /// The grace period is over and members are aborted
+    aborted: bool,
     ids: HashSet<ActivityId>,
+    /// Asks the supervisor to abort members that outlive the grace period
+    grace_timer: Option<JoinHandle<()>>,
 }
 
 impl Group {
@@ -93,10 +173,22 @@ impl Group {
     }
 }
 
+impl Drop for Group {
+    fn drop(&mut self) {
+        if let Some(timer) = self.grace_timer.take() {
+            timer.abort();
+        }
+    }
+}
+
 pub struct Tracker<S: Supervisor> {
     groups: BTreeMap<S::GroupBy, Group>,
     activities: TypedSlab<ActivityId, Activity<S>>,
+    /// Children that are available by keys
+    keys: HashMap<String, ActivityId>,
     terminating: bool,
+    /// Receives expired grace periods
+    grace_expired: Option<Recipient<GracePeriodExpired<S>>>,
 }
 
 impl<S: Supervisor> Default for Tracker<S> {
@@ -110,7 +202,9 @@ impl<S: Supervisor> Tracker<S> {
         Self {
             groups: BTreeMap::new(),
             activities: TypedSlab::new(),
+            keys: HashMap::new(),
             terminating: false,
+            grace_expired: None,
         }
     }
 
@@ -118,6 +212,10 @@ impl<S: Supervisor> Tracker<S> {
         self.groups.is_empty() && self.activities.is_empty()
     }
 
+    pub fn is_terminating(&self) -> bool {
+        self.terminating
+    }
+
     pub fn is_terminated(&self) -> bool {
         self.terminating && self.is_empty()
     }
@@ -132,27 +230,120 @@ impl<S: Supervisor> Tracker<S> {
         }
     }
 
+    pub fn terminate_activity(&mut self, id: ActivityId) {
+        if let Some(activity) = self.activities.get_mut(id) {
+            activity.interrupt();
+        }
+    }
+
     pub fn terminate_all(&mut self) {
         self.try_terminate_next();
+        // Deferred activities are interrupted already and finish immediately
+        let ids: Vec<_> = self.activities.iter().map(|(id, _)| id).collect();
+        for id in ids {
+            self.start_activity(id);
+        }
     }
 
-    fn register_activity(&mut self, group: S::GroupBy, interruptor: Interruptor) -> Relation<S> {
-        let activity = Activity {
-            group: group.clone(),
-            interruptor,
-        };
+    /// Checks whether all members of the group are started and ready.
+    pub fn is_ready(&self, group: &S::GroupBy) -> bool {
+        self.groups.get(group).is_none_or(|group| {
+            group
+                .ids
+                .iter()
+                .filter_map(|id| self.activities.get(*id))
+                .all(|activity| activity.ready && activity.start.is_none())
+        })
+    }
+
+    /// Checks whether members of the group can be started.
+    fn can_start(&self, group: &S::GroupBy) -> bool {
+        !S::ORDERED_STARTUP
+            || self
+                .groups
+                .range(..group)
+                .all(|(prev, _)| self.is_ready(prev))
+    }
+
+    fn start_activity(&mut self, id: ActivityId) {
+        if let Some(activity) = self.activities.get_mut(id) {
+            if let Some(start) = activity.start.take() {
+                activity.started = Some(Instant::now());
+                let rel = Relation {
+                    id,
+                    group: activity.group.clone(),
+                };
+                start.send(rel).ok();
+            }
+        }
+    }
+
+    /// Returns the address of the child spawned with the `key`.
+    pub fn child<A: Agent>(&self, key: &str) -> Option<Address<A>> {
+        let id = self.keys.get(key)?;
+        self.activities.get(*id)?.address().cloned()
+    }
+
+    /// Iterates over keyed children of the type `A` in the group.
+    pub fn children_in<'a, A: Agent>(
+        &'a self,
+        group: &S::GroupBy,
+    ) -> impl Iterator<Item = (&'a str, &'a Address<A>)> + 'a {
+        self.groups
+            .get(group)
+            .into_iter()
+            .flat_map(|group| group.ids.iter())
+            .filter_map(|id| self.activities.get(*id))
+            .filter_map(|activity| Some((activity.key.as_deref()?, activity.address()?)))
+    }
+
+    pub(crate) fn activities(&self) -> impl Iterator<Item = (ActivityId, &Activity<S>)> {
+        self.activities.iter()
+    }
+
+    fn mark_ready(&mut self, id: ActivityId) {
+        if let Some(activity) = self.activities.get_mut(id) {
+            activity.ready = true;
+        }
+    }
+
+    /// Starts deferred activities of groups that are allowed to start.
+    fn start_deferred(&mut self) {
+        let groups: Vec<_> = self.groups.keys().cloned().collect();
+        for group in groups {
+            if !self.can_start(&group) {
+                break;
+            }
+            let ids: Vec<_> = self.groups[&group].ids.iter().copied().collect();
+            for id in ids {
+                self.start_activity(id);
+            }
+        }
+    }
+
+    fn register_activity(&mut self, activity: Activity<S>) -> Relation<S> {
+        let group = activity.group.clone();
         let id = self.activities.insert(activity);
         let group_record = self.groups.entry(group.clone()).or_default();
         group_record.ids.insert(id);
-        if group_record.interrupted {
+        if group_record.aborted {
+            // Abort if the grace period of the group is over
+            self.activities.get_mut(id).map(Activity::abort);
+        } else if group_record.interrupted {
             // Interrupt if the group is terminating
             self.activities.get_mut(id).map(Activity::interrupt);
         }
+        if self.can_start(&group) || self.terminating {
+            self.start_activity(id);
+        }
         Relation { id, group }
     }
 
     fn unregister_activity(&mut self, rel: &Relation<S>) {
         if let Some(activity) = self.activities.remove(rel.id) {
+            if let Some(key) = activity.key {
+                self.keys.remove(&key);
+            }
             // TODO: check rel.group == activity.group ?
             if let Some(group) = self.groups.get_mut(&activity.group) {
                 group.ids.remove(&rel.id);
@@ -182,6 +373,19 @@ impl<S: Supervisor> Tracker<S> {
                             activity.interrupt();
                         }
                     }
+                    let grace_period = S::grace_period(&group_name);
+                    if let (Some(grace_period), Some(recipient)) =
+                        (grace_period, self.grace_expired.clone())
+                    {
+                        let msg = GracePeriodExpired {
+                            group: group_name.clone(),
+                        };
+                        let timer = crb_core::spawn(async move {
+                            sleep(grace_period).await;
+                            recipient.send(msg).ok();
+                        });
+                        group.grace_timer = Some(timer);
+                    }
                 }
                 if !group.is_finished() {
                     break;
@@ -189,6 +393,36 @@ impl<S: Supervisor> Tracker<S> {
             }
         }
     }
+
+    /// Aborts the current and the future members of the group.
+    fn abort_group(&mut self, group: &S::GroupBy) {
+        if let Some(group) = self.groups.get_mut(group) {
+            group.aborted = true;
+            for id in group.ids.iter() {
+                if let Some(activity) = self.activities.get_mut(*id) {
+                    activity.abort();
+                }
+            }
+        }
+    }
+}
+
+struct GracePeriodExpired<S: Supervisor> {
+    group: S::GroupBy,
+}
+
+#[async_trait]
+impl<S> MessageFor<S> for GracePeriodExpired<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    async fn handle(self: Box<Self>, _agent: &mut S, ctx: &mut S::Context) -> Result<(), Error> {
+        SupervisorContext::session(ctx)
+            .tracker
+            .abort_group(&self.group);
+        Ok(())
+    }
 }
 
 impl<S> SupervisorSession<S>
@@ -206,7 +440,91 @@ where
         A::Context: Default,
     {
         let runtime = RunAgent::<A>::new(input);
-        self.spawn_runtime(runtime, group)
+        self.spawn_agent_runtime(runtime, group)
+    }
+
+    /// Spawns an agent that is available by the `key` with
+    /// `child` and `children_in` until it detaches.
+    pub fn spawn_keyed<A>(
+        &mut self,
+        key: impl Into<String>,
+        input: A,
+        group: S::GroupBy,
+    ) -> Result<(Address<A>, Relation<S>)>
+    where
+        A: Agent,
+        A::Context: Default,
+    {
+        self.spawn_keyed_with_context(key, input, A::Context::default(), group)
+    }
+
+    /// Spawns an agent with the `context` that is available by the `key`.
+    pub fn spawn_keyed_with_context<A>(
+        &mut self,
+        key: impl Into<String>,
+        input: A,
+        context: A::Context,
+        group: S::GroupBy,
+    ) -> Result<(Address<A>, Relation<S>)>
+    where
+        A: Agent,
+    {
+        let key = key.into();
+        if self.tracker.keys.contains_key(&key) {
+            return Err(err!("The child with the key {key} already exists"));
+        }
+        let (address, rel) = self.spawn_agent_with_context(input, context, group);
+        if let Some(activity) = self.tracker.activities.get_mut(rel.id) {
+            activity.key = Some(key.clone());
+            self.tracker.keys.insert(key, rel.id);
+        }
+        Ok((address, rel))
+    }
+
+    pub fn spawn_agent_with_context<A>(
+        &mut self,
+        input: A,
+        context: A::Context,
+        group: S::GroupBy,
+    ) -> (<A::Context as Context>::Address, Relation<S>)
+    where
+        A: Agent,
+    {
+        let runtime = RunAgent::<A>::with_context(input, context);
+        self.spawn_agent_runtime(runtime, group)
+    }
+
+    /// Spawns an agent that inherits the dead letters sink of the supervisor
+    /// if it has no own sink.
+    fn spawn_agent_runtime<A>(
+        &mut self,
+        mut runtime: RunAgent<A>,
+        group: S::GroupBy,
+    ) -> (<A::Context as Context>::Address, Relation<S>)
+    where
+        A: Agent,
+    {
+        let child = runtime.context.session();
+        if child.dead_letters().is_none() {
+            child.set_dead_letters(self.session.dead_letters());
+        }
+        let address = runtime.address();
+        let child = address.downgrade();
+        let outcome = move || ChildOutcome::of_agent(&child);
+        let readiness = S::ORDERED_STARTUP.then(|| {
+            let address = address.clone();
+            let fut: BoxFuture<'static, bool> =
+                Box::pin(async move { address.ready().await.is_ok() });
+            fut
+        });
+        let meta = ChildMeta {
+            type_name: type_name::<A>(),
+            agent_id: Some(address.id()),
+            address: Some(Box::new(address.clone())),
+            readiness,
+        };
+        let rel = self.spawn_with_outcome(runtime, group, outcome, meta);
+        (address, rel)
     }
 
     pub fn spawn_runtime<B>(
@@ -222,38 +540,161 @@ where
         (addr, rel)
     }
 
-    pub fn spawn_trackable<B>(&mut self, mut trackable: B, group: S::GroupBy) -> Relation<S>
+    pub fn spawn_trackable<B>(&mut self, trackable: B, group: S::GroupBy) -> Relation<S>
     where
         B: Runtime,
     {
-        let interruptor = trackable.get_interruptor();
-        let rel = self.tracker.register_activity(group, interruptor);
-        let detacher = DetacherFor {
-            supervisor: self.address().clone(),
-            rel: rel.clone(),
+        let meta = ChildMeta {
+            type_name: type_name::<B>(),
+            agent_id: None,
+            address: None,
+            readiness: None,
         };
+        self.spawn_with_outcome(trackable, group, ChildOutcome::done, meta)
+    }
+
+    /// Spawns the activity that waits until previous groups are ready.
+    fn spawn_with_outcome<B, F>(
+        &mut self,
+        mut trackable: B,
+        group: S::GroupBy,
+        outcome: F,
+        meta: ChildMeta,
+    ) -> Relation<S>
+    where
+        B: Runtime,
+        F: FnOnce() -> ChildOutcome + Send + 'static,
+    {
+        if self.inspector.is_none() {
+            // Makes the supervisor visible in snapshots of its parent
+            self.inspector = Some(InspectorGuard::register(self.address()));
+            let recipient = Recipient::new(self.address().downgrade());
+            self.tracker.grace_expired = Some(recipient);
+        }
+        let ChildMeta {
+            type_name,
+            agent_id,
+            address,
+            readiness,
+        } = meta;
+        let interruptor = trackable.get_interruptor();
+        // Children don't keep the supervisor alive
+        let supervisor = self.address().downgrade();
+        let ready = readiness.is_none();
+        let (start, started) = oneshot::channel::<Relation<S>>();
 
         let fut = async move {
+            // The tracker sends the relation when the activity can start
+            let Ok(rel) = started.await else {
+                return;
+            };
+            if let Some(readiness) = readiness {
+                let supervisor = supervisor.clone();
+                let id = rel.id;
+                crb_core::spawn(async move {
+                    if readiness.await {
+                        supervisor.send(MarkReady { id }).ok();
+                    }
+                });
+            }
+            let detacher = DetacherFor { supervisor, rel };
             trackable.routine().await;
             // This notification equals calling `detach_trackable`
-            if let Err(err) = detacher.detach() {
+            if let Err(err) = detacher.detach(outcome()) {
                 log::error!("Can't notify a supervisor to detach an activity: {err}");
             }
         };
-        crb_core::spawn(fut);
-        rel
+        let handle = crb_core::spawn(fut);
+        let activity = Activity {
+            group,
+            job: JobHandle::new(interruptor, handle),
+            start: Some(start),
+            ready,
+            interrupted: false,
+            type_name,
+            agent_id,
+            started: None,
+            address,
+            key: None,
+        };
+        self.tracker.register_activity(activity)
     }
 }
 
-struct Activity<S: Supervisor> {
-    group: S::GroupBy,
-    // TODO: Consider to use JobHandle here
-    interruptor: Interruptor,
+struct ChildMeta {
+    type_name: &'static str,
+    /// The id of the address of an agent
+    agent_id: Option<Uuid>,
+    /// The address of an agent that keeps it alive while it's attached
+    address: Option<Box<dyn Any + Send>>,
+    /// Resolves to `true` when the child is ready.
+    /// A child without it is ready immediately.
+    readiness: Option<BoxFuture<'static, bool>>,
+}
+
+struct MarkReady {
+    id: ActivityId,
+}
+
+#[async_trait]
+impl<S> MessageFor<S> for MarkReady
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    async fn handle(self: Box<Self>, _agent: &mut S, ctx: &mut S::Context) -> Result<(), Error> {
+        let session = SupervisorContext::session(ctx);
+        session.tracker.mark_ready(self.id);
+        session.tracker.start_deferred();
+        Ok(())
+    }
+}
+
+pub(crate) struct Activity<S: Supervisor> {
+    pub(crate) group: S::GroupBy,
+    job: JobHandle,
+    /// Starts the activity when previous groups are ready
+    start: Option<oneshot::Sender<Relation<S>>>,
+    ready: bool,
+    interrupted: bool,
+    pub(crate) type_name: &'static str,
+    pub(crate) agent_id: Option<Uuid>,
+    started: Option<Instant>,
+    address: Option<Box<dyn Any + Send>>,
+    key: Option<String>,
 }
 
 impl<S: Supervisor> Activity<S> {
+    fn address<A: Agent>(&self) -> Option<&Address<A>> {
+        self.address.as_ref()?.downcast_ref()
+    }
+
     fn interrupt(&mut self) {
-        self.interruptor.stop(false);
+        self.interrupted = true;
+        self.job.interruptor().stop(false);
+    }
+
+    fn abort(&mut self) {
+        self.interrupted = true;
+        self.job.interrupt();
+    }
+
+    pub(crate) fn state(&self) -> ChildState {
+        if self.interrupted {
+            ChildState::Stopping
+        } else if self.start.is_some() {
+            ChildState::Waiting
+        } else if !self.ready {
+            ChildState::Starting
+        } else {
+            ChildState::Running
+        }
+    }
+
+    pub(crate) fn uptime(&self) -> Duration {
+        self.started
+            .map(|started| started.elapsed())
+            .unwrap_or_default()
     }
 }
 
@@ -271,8 +712,92 @@ impl<S: Supervisor> Clone for Relation<S> {
     }
 }
 
+/// How a child has finished.
+#[derive(Debug, Clone)]
+pub enum ChildStatus {
+    Done,
+    Interrupted,
+    Failed(Arc<Error>),
+}
+
+type TakeOutput = Box<dyn FnOnce() -> Option<Box<dyn Any + Send>> + Send>;
+
+struct ChildOutput {
+    type_id: TypeId,
+    take: TakeOutput,
+}
+
+/// The outcome of a child that is passed to `Supervisor::finished_with_outcome`.
+pub struct ChildOutcome {
+    pub status: ChildStatus,
+    output: Option<ChildOutput>,
+}
+
+impl ChildOutcome {
+    /// The outcome of a runtime that doesn't report its status.
+    pub fn done() -> Self {
+        Self {
+            status: ChildStatus::Done,
+            output: None,
+        }
+    }
+
+    fn of_agent<A: Agent>(child: &WeakAddress<A>) -> Self {
+        let status = match &*child.status() {
+            AgentStatus::Done(_) => ChildStatus::Done,
+            AgentStatus::Failed(err) => ChildStatus::Failed(err.clone()),
+            AgentStatus::Active | AgentStatus::Interrupted => ChildStatus::Interrupted,
+        };
+        let child = child.clone();
+        let take = move || {
+            let output = child.status().output()?.take()?;
+            Some(Box::new(output) as Box<dyn Any + Send>)
+        };
+        let output = ChildOutput {
+            type_id: TypeId::of::<A::Output>(),
+            take: Box::new(take),
+        };
+        Self {
+            status,
+            output: Some(output),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status, ChildStatus::Failed(_))
+    }
+
+    pub fn error(&self) -> Option<&Arc<Error>> {
+        match &self.status {
+            ChildStatus::Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Takes the output of the child agent, so it's not available
+    /// for its addresses anymore.
+    ///
+    /// Returns `None` if the type of the output doesn't match.
+    pub fn take_output<O: 'static>(&mut self) -> Option<O> {
+        if self.output.as_ref()?.type_id != TypeId::of::<O>() {
+            return None;
+        }
+        let output = (self.output.take()?.take)()?;
+        output.downcast().ok().map(|output| *output)
+    }
+}
+
+impl Debug for ChildOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildOutcome")
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
 struct DetachFrom<S: Supervisor> {
     rel: Relation<S>,
+    outcome: ChildOutcome,
 }
 
 #[async_trait]
@@ -282,19 +807,25 @@ where
     S::Context: SupervisorContext<S>,
 {
     async fn handle(self: Box<Self>, agent: &mut S, ctx: &mut S::Context) -> Result<(), Error> {
+        let DetachFrom { rel, outcome } = *self;
+        let strategy = agent.strategy();
+        let intensity = agent.intensity();
         let session = SupervisorContext::session(ctx);
-        session.tracker.unregister_activity(&self.rel);
-        if session.tracker.is_terminated() {
-            session.session.shutdown();
+        session.tracker.unregister_activity(&rel);
+        session.tracker.start_deferred();
+        let failed = outcome.is_failed();
+        match session.restart_children(&rel, failed, strategy, intensity) {
+            Ok(()) => session.finish_if_terminated(),
+            Err(err) => session.escalate(err),
         }
-        agent.finished(&self.rel, ctx);
+        agent.finished_with_outcome(&rel, outcome, ctx);
         Ok(())
     }
 }
 
 pub struct DetacherFor<S: Supervisor> {
     rel: Relation<S>,
-    supervisor: <S::Context as Context>::Address,
+    supervisor: WeakAddress<S>,
 }
 
 impl<S> DetacherFor<S>
@@ -302,8 +833,11 @@ where
     S: Supervisor,
     S::Context: SupervisorContext<S>,
 {
-    pub fn detach(self) -> Result<(), Error> {
-        let msg = DetachFrom { rel: self.rel };
-        self.supervisor.send(msg)
+    pub fn detach(self, outcome: ChildOutcome) -> Result<(), Error> {
+        let msg = DetachFrom {
+            rel: self.rel,
+            outcome,
+        };
+        self.supervisor.send_priority(msg)
     }
 }
@@ -1,9 +1,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use crb_agent::{Address, Agent, AgentSession, DoAsync, MessageFor, Next, RunAgent};
+use crb_agent::{Address, Agent, AgentSession, DoAsync, MessageFor, Next, RunAgent, WeakAddress};
 use crb_core::{
     time::{sleep, Duration},
-    Slot, SyncTag,
+    Slot, SyncTag, Tag,
 };
 use crb_runtime::{JobHandle, Task};
 use crb_send::{MessageSender, Sender};
@@ -24,10 +24,33 @@ impl Timeout {
         A: OnTimeout<T>,
         T: SyncTag,
     {
+        Self::message(address, duration, Completed { tag })
+    }
+
+    /// Sends the message to the agent when the duration has elapsed.
+    pub fn message<A, M>(address: Address<A>, duration: Duration, message: M) -> Self
+    where
+        A: Agent,
+        M: MessageFor<A>,
+    {
+        Self::with_sender(address.sender(), duration, message)
+    }
+
+    /// Sends the message to the agent when the duration has elapsed,
+    /// but doesn't keep the agent alive meanwhile.
+    pub fn weak_message<A, M>(address: WeakAddress<A>, duration: Duration, message: M) -> Self
+    where
+        A: Agent,
+        M: MessageFor<A>,
+    {
+        Self::with_sender(address.sender(), duration, message)
+    }
+
+    fn with_sender<M: Tag>(sender: MessageSender<M>, duration: Duration, message: M) -> Self {
         let task = TimeoutTask {
             duration,
-            tag: Slot::filled("timeout task tag", tag),
-            sender: address.sender(),
+            message: Slot::filled("timeout task message", message),
+            sender,
         };
         let mut job = RunAgent::new(task).spawn().job();
         job.cancel_on_drop(true);
@@ -35,15 +58,15 @@ impl Timeout {
     }
 }
 
-struct TimeoutTask<T> {
+struct TimeoutTask<M> {
     duration: Duration,
-    tag: Slot<T>,
-    sender: MessageSender<Completed<T>>,
+    message: Slot<M>,
+    sender: MessageSender<M>,
 }
 
-impl<T> Agent for TimeoutTask<T>
+impl<M> Agent for TimeoutTask<M>
 where
-    T: SyncTag,
+    M: Tag,
 {
     type Context = AgentSession<Self>;
     type Output = ();
@@ -54,16 +77,14 @@ where
 }
 
 #[async_trait]
-impl<T> DoAsync for TimeoutTask<T>
+impl<M> DoAsync for TimeoutTask<M>
 where
-    T: SyncTag,
+    M: Tag,
 {
     async fn once(&mut self, _: &mut ()) -> Result<Next<Self>> {
         sleep(self.duration).await;
-        let tick = Completed {
-            tag: self.tag.take()?,
-        };
-        self.sender.send(tick)?;
+        let message = self.message.take()?;
+        self.sender.send(message)?;
         Ok(Next::done())
     }
 }
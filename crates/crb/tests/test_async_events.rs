@@ -0,0 +1,68 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, DoAsyncWithEvents, Next, OnEvent, Standalone};
+use crb::runtime::Interruptor;
+use crb::superagent::{AddressExt, OnRequest, Request};
+use tokio::time::{sleep, timeout, Duration};
+
+struct Poller {
+    polls: usize,
+}
+
+impl Standalone for Poller {}
+
+impl Agent for Poller {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async_with_events(LongPoll)
+    }
+}
+
+struct LongPoll;
+
+#[async_trait]
+impl DoAsyncWithEvents<LongPoll> for Poller {
+    async fn perform(_state: &mut LongPoll, _interruptor: Interruptor) -> Result<()> {
+        sleep(Duration::from_secs(60)).await;
+        Ok(())
+    }
+}
+
+struct Status;
+
+impl Request for Status {
+    type Response = usize;
+}
+
+#[async_trait]
+impl OnRequest<Status> for Poller {
+    async fn on_request(&mut self, _: Status, _ctx: &mut Self::Context) -> Result<usize> {
+        self.polls += 1;
+        Ok(self.polls)
+    }
+}
+
+struct Cancel;
+
+#[async_trait]
+impl OnEvent<Cancel> for Poller {
+    async fn handle(&mut self, _: Cancel, ctx: &mut Self::Context) -> Result<()> {
+        ctx.do_next(Next::interrupt());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_async_events() -> Result<()> {
+    let mut addr = Poller { polls: 0 }.spawn();
+    let duration = Duration::from_secs(5);
+    let polls = timeout(duration, addr.interact(Status)).await??;
+    assert_eq!(polls, 1);
+    let polls = timeout(duration, addr.interact(Status)).await??;
+    assert_eq!(polls, 2);
+    addr.event(Cancel)?;
+    timeout(duration, addr.join()).await??;
+    Ok(())
+}
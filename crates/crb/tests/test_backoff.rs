@@ -0,0 +1,219 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, DoAsync, Next, Standalone, SupervisorSession};
+use crb::superagent::{Backoff, ChildSpec, Intensity, Strategy, Supervisor, SupervisorContext};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+type Records = Journal<(&'static str, &'static str, Instant)>;
+
+/// Waits until the condition is met. The time is paused in tests,
+/// so the waiting takes no time if the condition is never met.
+async fn wait_until(condition: impl Fn() -> bool) -> Result<()> {
+    let waiting = async {
+        while !condition() {
+            sleep(Duration::from_millis(1)).await;
+        }
+    };
+    timeout(Duration::from_secs(60), waiting).await?;
+    Ok(())
+}
+
+fn count(records: &Records, name: &str, event: &str) -> usize {
+    let records = records.lock().unwrap();
+    records
+        .iter()
+        .filter(|(n, e, _)| *n == name && *e == event)
+        .count()
+}
+
+fn time_of(records: &Records, name: &str, event: &str, nth: usize) -> Instant {
+    let records = records.lock().unwrap();
+    records
+        .iter()
+        .filter(|(n, e, _)| *n == name && *e == event)
+        .nth(nth)
+        .map(|(_, _, time)| *time)
+        .unwrap()
+}
+
+/// Works for a while and fails during the first `failures` starts.
+/// Keeps running afterwards until it's interrupted.
+struct Worker {
+    name: &'static str,
+    works: Duration,
+    failures: usize,
+    records: Records,
+    failing: bool,
+}
+
+impl Worker {
+    fn spec<S>(self, group: S::GroupBy) -> ChildSpec<S>
+    where
+        S: Supervisor,
+        S::Context: SupervisorContext<S>,
+    {
+        let Self {
+            name,
+            works,
+            failures,
+            records,
+            ..
+        } = self;
+        ChildSpec::new(group, move || Worker {
+            name,
+            works,
+            failures,
+            records: records.clone(),
+            failing: false,
+        })
+    }
+
+    fn record(&self, event: &'static str) -> usize {
+        self.records
+            .lock()
+            .unwrap()
+            .push((self.name, event, Instant::now()));
+        count(&self.records, self.name, event)
+    }
+}
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        self.failing = self.record("start") <= self.failures;
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Worker {
+    async fn repeat(&mut self, _: &mut ()) -> Result<Option<Next<Self>>> {
+        if self.failing {
+            sleep(self.works).await;
+            self.record("fail");
+            Ok(Some(Next::fail(Error::msg("Broken"))))
+        } else {
+            sleep(Duration::from_millis(10)).await;
+            Ok(None)
+        }
+    }
+}
+
+impl Standalone for Worker {}
+
+/// Starts children by specs.
+struct Team {
+    children: Vec<ChildSpec<Self>>,
+    strategy: Strategy,
+    intensity: Intensity,
+}
+
+impl Team {
+    fn new(strategy: Strategy, max_restarts: usize, within: Duration) -> Self {
+        Self {
+            children: Vec::new(),
+            strategy,
+            intensity: Intensity {
+                max_restarts,
+                within,
+            },
+        }
+    }
+
+    fn child(mut self, spec: ChildSpec<Self>) -> Self {
+        self.children.push(spec);
+        self
+    }
+}
+
+impl Standalone for Team {}
+
+impl Agent for Team {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        for spec in self.children.drain(..) {
+            ctx.spawn_child(spec);
+        }
+        Next::events()
+    }
+}
+
+impl Supervisor for Team {
+    type GroupBy = ();
+
+    fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    fn intensity(&self) -> Intensity {
+        self.intensity
+    }
+}
+
+fn worker(name: &'static str, works: u64, failures: usize, records: &Records) -> Worker {
+    Worker {
+        name,
+        works: Duration::from_millis(works),
+        failures,
+        records: records.clone(),
+        failing: false,
+    }
+}
+
+fn no_jitter(initial: u64) -> Backoff {
+    Backoff {
+        initial: Duration::from_millis(initial),
+        jitter: 0.0,
+        ..Backoff::default()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff() -> Result<()> {
+    let records = Records::default();
+    let spec = worker("worker", 0, usize::MAX, &records).spec(());
+    let mut team = Team::new(Strategy::OneForOne, 10, Duration::from_secs(60))
+        .child(spec.backoff(no_jitter(40)))
+        .spawn();
+    wait_until(|| count(&records, "worker", "start") == 4).await?;
+    team.interrupt()?;
+    timeout(Duration::from_secs(5), team.join()).await??;
+
+    for (attempt, delay) in [40, 80, 160].into_iter().enumerate() {
+        let gap = time_of(&records, "worker", "start", attempt + 1)
+            - time_of(&records, "worker", "fail", attempt);
+        assert!(gap >= Duration::from_millis(delay));
+        assert!(gap < Duration::from_millis(delay + 5));
+    }
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff_per_child() -> Result<()> {
+    let records = Records::default();
+    let fast = worker("fast", 0, 1, &records).spec(());
+    let slow = worker("slow", 50, 1, &records).spec(());
+    let mut team = Team::new(Strategy::OneForOne, 10, Duration::from_secs(60))
+        .child(fast.backoff(no_jitter(100)))
+        .child(slow.backoff(no_jitter(300)))
+        .spawn();
+    wait_until(|| count(&records, "slow", "start") == 2).await?;
+    team.interrupt()?;
+    timeout(Duration::from_secs(5), team.join()).await??;
+
+    // Every child waits for its own delay
+    let fast = time_of(&records, "fast", "start", 1) - time_of(&records, "fast", "fail", 0);
+    assert!(fast >= Duration::from_millis(100));
+    assert!(fast < Duration::from_millis(105));
+    let slow = time_of(&records, "slow", "start", 1) - time_of(&records, "slow", "fail", 0);
+    assert!(slow >= Duration::from_millis(300));
+    assert!(slow < Duration::from_millis(305));
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{Agent, AgentSession, ManagedContext, OnEvent, RunAgent, Task};
+use crb::core::mpsc::error::TrySendError;
+use crb::runtime::InteractiveRuntime;
+
+struct Counter {
+    counter: usize,
+}
+
+impl Agent for Counter {
+    type Context = AgentSession<Self>;
+    type Output = usize;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.counter)
+    }
+}
+
+struct Increment;
+
+#[async_trait]
+impl OnEvent<Increment> for Counter {
+    async fn handle(&mut self, _: Increment, _ctx: &mut Self::Context) -> Result<()> {
+        self.counter += 1;
+        Ok(())
+    }
+}
+
+struct Stop;
+
+#[async_trait]
+impl OnEvent<Stop> for Counter {
+    async fn handle(&mut self, _: Stop, ctx: &mut Self::Context) -> Result<()> {
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_bounded() -> Result<()> {
+    let runtime = RunAgent::new(Counter { counter: 0 }).bounded(2);
+    let mut addr = runtime.address();
+    assert_eq!(addr.capacity(), Some(2));
+
+    addr.event(Increment)?;
+    assert!(addr.try_send(Event::new(Increment)).is_ok());
+    let res = addr.try_send(Event::new(Increment));
+    assert!(matches!(res, Err(TrySendError::Full(_))));
+    assert!(addr.event(Increment).is_err());
+
+    runtime.spawn();
+    addr.send_async(Event::new(Increment)).await?;
+    addr.send_async(Event::new(Stop)).await?;
+    let output = addr.join().await?.output();
+    assert_eq!(output, Some(3));
+    Ok(())
+}
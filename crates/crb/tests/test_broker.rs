@@ -0,0 +1,77 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Next, OnEvent, Standalone};
+use crb::superagent::{Broker, BrokerAddress};
+use tokio::time::{timeout, Duration};
+
+#[derive(Clone)]
+struct Temperature(i32);
+
+#[derive(Default)]
+struct Display {
+    values: Vec<i32>,
+}
+
+impl Standalone for Display {}
+
+impl Agent for Display {
+    type Context = AgentSession<Self>;
+    type Output = Vec<i32>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.values)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Temperature> for Display {
+    async fn handle(&mut self, event: Temperature, ctx: &mut Self::Context) -> Result<()> {
+        self.values.push(event.0);
+        if self.values.len() == 2 {
+            ctx.do_next(Next::done());
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_broker() -> Result<()> {
+    let broker = Broker::default().spawn();
+    let mut first = Display::default().spawn();
+    broker.subscribe_agent::<_, Temperature>("outside", &first)?;
+    broker.publish_retained("outside", Temperature(20))?;
+    broker.publish("inside", Temperature(25))?;
+
+    let mut late = Display::default().spawn();
+    broker.subscribe_agent::<_, Temperature>("outside", &late)?;
+    broker.publish("outside", Temperature(21))?;
+
+    let duration = Duration::from_secs(5);
+    let values = timeout(duration, first.take_output()).await??;
+    assert_eq!(values, Some(vec![20, 21]));
+    let values = timeout(duration, late.take_output()).await??;
+    assert_eq!(values, Some(vec![20, 21]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retained_to_stopped() -> Result<()> {
+    let mut broker = Broker::default().spawn();
+    broker.publish_retained("outside", Temperature(20))?;
+
+    let mut stopped = Display::default().spawn();
+    let recipient = stopped.recipient::<Temperature>();
+    stopped.interrupt()?;
+    stopped.join().await?;
+    broker.subscribe("outside", recipient)?;
+
+    let mut display = Display::default().spawn();
+    broker.subscribe_agent::<_, Temperature>("outside", &display)?;
+    broker.publish("outside", Temperature(21))?;
+    let values = timeout(Duration::from_secs(5), display.take_output()).await??;
+    assert_eq!(values, Some(vec![20, 21]));
+
+    broker.interrupt()?;
+    assert!(!broker.join().await?.status().is_failed());
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use crb::agent::{Agent, AgentSession, Consume, Next, Runnable};
+
+struct Greeter {
+    name: String,
+    direct: bool,
+}
+
+impl Agent for Greeter {
+    type Context = AgentSession<Self>;
+    type Output = String;
+
+    fn begin(&mut self) -> Next<Self> {
+        if self.direct {
+            Next::output(format!("Hi, {}!", self.name))
+        } else {
+            Next::consume()
+        }
+    }
+}
+
+impl Consume for Greeter {
+    fn consume(self) -> Self::Output {
+        format!("Hello, {}!", self.name)
+    }
+}
+
+#[tokio::test]
+async fn test_consume() -> Result<()> {
+    let name = "Consumer".to_string();
+    let greeter = Greeter { name, direct: true };
+    let output = greeter.run().await?;
+    assert_eq!(output.as_deref(), Some("Hi, Consumer!"));
+
+    let name = "Consumer".to_string();
+    let greeter = Greeter {
+        name,
+        direct: false,
+    };
+    let output = greeter.run().await?;
+    assert_eq!(output.as_deref(), Some("Hello, Consumer!"));
+    Ok(())
+}
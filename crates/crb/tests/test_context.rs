@@ -0,0 +1,99 @@
+use anyhow::Result;
+use crb::agent::{
+    Address, Agent, AgentContext, AgentSession, Context, ManagedContext, Next, Standalone,
+};
+use crb::superagent::{Relation, Supervisor, SupervisorSession};
+use derive_more::{Deref, DerefMut};
+
+#[derive(Deref, DerefMut)]
+struct GreeterContext {
+    #[deref]
+    #[deref_mut]
+    session: AgentSession<Greeter>,
+    greeting: String,
+}
+
+impl GreeterContext {
+    fn new(greeting: &str) -> Self {
+        Self {
+            session: AgentSession::default(),
+            greeting: greeting.into(),
+        }
+    }
+}
+
+impl Context for GreeterContext {
+    type Address = Address<Greeter>;
+
+    fn address(&self) -> &Self::Address {
+        self.session.address()
+    }
+}
+
+impl ManagedContext for GreeterContext {
+    fn is_alive(&self) -> bool {
+        self.session.is_alive()
+    }
+
+    fn shutdown(&mut self) {
+        self.session.shutdown();
+    }
+
+    fn stop(&mut self) {
+        self.session.stop();
+    }
+}
+
+impl AgentContext<Greeter> for GreeterContext {
+    fn session(&mut self) -> &mut AgentSession<Greeter> {
+        &mut self.session
+    }
+}
+
+struct Greeter;
+
+impl Standalone for Greeter {}
+
+impl Agent for Greeter {
+    type Context = GreeterContext;
+    type Output = String;
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        Next::output(format!("{}, Context!", ctx.greeting))
+    }
+}
+
+struct Main;
+
+impl Standalone for Main {}
+
+impl Agent for Main {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        let context = GreeterContext::new("Hi");
+        ctx.spawn_agent_with_context(Greeter, context, ());
+        Next::events()
+    }
+}
+
+impl Supervisor for Main {
+    type GroupBy = ();
+
+    fn finished(&mut self, _rel: &Relation<Self>, ctx: &mut Self::Context) {
+        ctx.shutdown();
+    }
+}
+
+#[tokio::test]
+async fn test_context() -> Result<()> {
+    let context = GreeterContext::new("Hello");
+    let mut addr = Greeter.spawn_with_context(context);
+    let output = addr.join().await?.output();
+    assert_eq!(output.as_deref(), Some("Hello, Context!"));
+
+    let mut addr = Main.spawn();
+    addr.join().await?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::dead_letter::set_global_sink;
+use crb::agent::message::event::Event;
+use crb::agent::{
+    Agent, AgentSession, DeadLetter, Next, OnDeadLetter, OnEvent, RunAgent, Runnable, Standalone,
+    Task,
+};
+use crb::core::uuid::Uuid;
+use crb::runtime::InteractiveRuntime;
+use crb::send::Recipient;
+use tokio::time::{timeout, Duration};
+
+#[derive(Default)]
+struct Collector {
+    letters: Vec<(Uuid, u8)>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = AgentSession<Self>;
+    type Output = Vec<(Uuid, u8)>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.letters)
+    }
+}
+
+#[async_trait]
+impl OnDeadLetter for Collector {
+    async fn on_dead_letter(&mut self, letter: DeadLetter, ctx: &mut Self::Context) -> Result<()> {
+        let target = letter.target();
+        let ping = letter
+            .into_envelope::<Worker>()
+            .ok()
+            .and_then(|envelope| envelope.into_message::<Event<Ping>>().ok());
+        if let Some(ping) = ping {
+            self.letters.push((target, ping.into_inner().0));
+        }
+        if self.letters.len() == 3 {
+            ctx.do_next(Next::done());
+        }
+        Ok(())
+    }
+}
+
+/// Stops by itself when it has pings in the mailbox.
+#[derive(Default)]
+struct Worker {
+    pings: Vec<u8>,
+    queue: Vec<u8>,
+}
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        if !self.queue.is_empty() {
+            ctx.event(Stop).ok();
+        }
+        for ping in self.queue.drain(..) {
+            ctx.event(Ping(ping)).ok();
+        }
+        Next::events()
+    }
+}
+
+struct Ping(u8);
+
+#[async_trait]
+impl OnEvent<Ping> for Worker {
+    async fn handle(&mut self, ping: Ping, _ctx: &mut Self::Context) -> Result<()> {
+        self.pings.push(ping.0);
+        Ok(())
+    }
+}
+
+struct Stop;
+
+#[async_trait]
+impl OnEvent<Stop> for Worker {
+    async fn handle(&mut self, _: Stop, ctx: &mut Self::Context) -> Result<()> {
+        ctx.do_next(Next::done());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_dead_letter() -> Result<()> {
+    let mut collector = Collector::default().spawn();
+    let mut runtime = RunAgent::new(Worker::default());
+    let sink = Recipient::new(collector.clone());
+    runtime.context.set_dead_letters(Some(sink));
+    let mut addr = runtime.address();
+    let id = addr.id();
+
+    addr.event(Stop)?;
+    addr.event(Ping(1))?;
+    addr.event(Ping(2))?;
+    runtime.spawn();
+    addr.join().await?;
+    // Delivered to the sink whether the mailbox is drained or closed
+    addr.event(Ping(3)).ok();
+
+    let letters = timeout(Duration::from_secs(5), collector.take_output()).await??;
+    assert_eq!(letters, Some(vec![(id, 1), (id, 2), (id, 3)]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_global_sink() -> Result<()> {
+    let mut collector = Collector::default().spawn();
+    set_global_sink(Some(Recipient::new(collector.clone())));
+
+    let worker = Worker {
+        pings: Vec::new(),
+        queue: vec![4, 5, 6],
+    };
+    // Agents that are run directly drain their mailboxes too
+    worker.run().await?;
+
+    let letters = timeout(Duration::from_secs(5), collector.take_output()).await??;
+    set_global_sink(None);
+    let pings: Vec<_> = letters.unwrap().into_iter().map(|(_, ping)| ping).collect();
+    assert_eq!(pings, [4, 5, 6]);
+    Ok(())
+}
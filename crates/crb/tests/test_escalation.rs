@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Error, Result};
+use crb::agent::{Agent, AgentSession, ManagedContext, Next, Standalone, SupervisorSession};
+use crb::superagent::{ChildOutcome, Relation, Supervisor};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+struct Idle;
+
+impl Agent for Idle {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+/// Fails right after the start.
+struct Broken;
+
+impl Agent for Broken {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::fail(Error::msg("Broken"))
+    }
+}
+
+/// Escalates failures of its children.
+struct Manager;
+
+impl Agent for Manager {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Idle, ());
+        ctx.spawn_agent(Broken, ());
+        Next::events()
+    }
+}
+
+impl Supervisor for Manager {
+    type GroupBy = ();
+
+    fn grace_period(_group: &()) -> Option<Duration> {
+        Some(Duration::from_millis(10))
+    }
+
+    fn finished_with_outcome(
+        &mut self,
+        _rel: &Relation<Self>,
+        outcome: ChildOutcome,
+        ctx: &mut Self::Context,
+    ) {
+        if let Some(err) = outcome.error() {
+            ctx.escalate(anyhow!("The worker has failed: {err}"));
+        }
+    }
+}
+
+struct Director {
+    errors: Journal<String>,
+}
+
+impl Standalone for Director {}
+
+impl Agent for Director {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Manager, ());
+        Next::events()
+    }
+}
+
+impl Supervisor for Director {
+    type GroupBy = ();
+
+    fn finished_with_outcome(
+        &mut self,
+        _rel: &Relation<Self>,
+        outcome: ChildOutcome,
+        ctx: &mut Self::Context,
+    ) {
+        if let Some(err) = outcome.error() {
+            self.errors.lock().unwrap().push(err.to_string());
+        }
+        ctx.shutdown();
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_escalation() -> Result<()> {
+    let errors = Journal::default();
+    let mut director = Director {
+        errors: errors.clone(),
+    }
+    .spawn();
+    timeout(Duration::from_secs(5), director.join()).await??;
+    assert_eq!(*errors.lock().unwrap(), ["The worker has failed: Broken"]);
+    Ok(())
+}
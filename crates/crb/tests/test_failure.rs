@@ -0,0 +1,41 @@
+use anyhow::{Error, Result};
+use crb::agent::{Agent, AgentSession, DoSync, Next, Standalone};
+
+struct Faulty {
+    panic: bool,
+}
+
+impl Standalone for Faulty {}
+
+impl Agent for Faulty {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        if self.panic {
+            Next::do_sync(())
+        } else {
+            Next::fail(Error::msg("Faulty has failed"))
+        }
+    }
+}
+
+impl DoSync for Faulty {
+    fn once(&mut self, _: &mut ()) -> Result<Next<Self>> {
+        panic!("Faulty has crashed");
+    }
+}
+
+#[tokio::test]
+async fn test_failure() -> Result<()> {
+    let mut addr = Faulty { panic: false }.spawn();
+    let output = addr.join().await?;
+    assert!(output.status().is_failed());
+    let err = output.error().map(|err| err.to_string());
+    assert_eq!(err.as_deref(), Some("Faulty has failed"));
+
+    let mut addr = Faulty { panic: true }.spawn();
+    let output = addr.join().await?;
+    assert!(output.status().is_failed());
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, DoAsync, Next, OnEvent, Standalone, SupervisorSession};
+use crb::runtime::Interruptor;
+use crb::superagent::{ChildOutcome, ChildStatus, Relation, Supervisor};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+struct Idle;
+
+impl Agent for Idle {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+/// Ignores interruptions.
+struct Stubborn;
+
+impl Agent for Stubborn {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Stubborn {
+    async fn perform(&mut self, _: (), _interruptor: Interruptor) -> Next<Self> {
+        sleep(Duration::from_secs(60)).await;
+        Next::done()
+    }
+}
+
+/// Keeps a stubborn child in the group 1.
+struct Shutdown {
+    journal: Journal<(u8, bool)>,
+}
+
+impl Standalone for Shutdown {}
+
+impl Agent for Shutdown {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Idle, 0);
+        ctx.spawn_agent(Stubborn, 1);
+        Next::events()
+    }
+}
+
+impl Supervisor for Shutdown {
+    type GroupBy = u8;
+
+    fn grace_period(_group: &u8) -> Option<Duration> {
+        Some(Duration::from_millis(50))
+    }
+
+    fn finished_with_outcome(
+        &mut self,
+        rel: &Relation<Self>,
+        outcome: ChildOutcome,
+        _ctx: &mut Self::Context,
+    ) {
+        let interrupted = matches!(outcome.status, ChildStatus::Interrupted);
+        self.journal.lock().unwrap().push((rel.group, interrupted));
+    }
+}
+
+/// Spawns a stubborn child into the terminating group.
+struct Late;
+
+#[async_trait]
+impl OnEvent<Late> for Shutdown {
+    async fn handle(&mut self, _: Late, ctx: &mut Self::Context) -> Result<()> {
+        ctx.spawn_agent(Stubborn, 1);
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_grace_period() -> Result<()> {
+    let journal = Journal::default();
+    let mut shutdown = Shutdown {
+        journal: journal.clone(),
+    }
+    .spawn();
+    sleep(Duration::from_millis(10)).await;
+    let interrupted = Instant::now();
+    shutdown.interrupt()?;
+    timeout(Duration::from_secs(5), shutdown.join()).await??;
+    // Groups are aborted one after another in the reverse order
+    assert_eq!(*journal.lock().unwrap(), [(1, true), (0, true)]);
+    assert!(interrupted.elapsed() >= Duration::from_millis(100));
+    assert!(interrupted.elapsed() < Duration::from_millis(110));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_children_added_during_shutdown() -> Result<()> {
+    let journal = Journal::default();
+    let mut shutdown = Shutdown {
+        journal: journal.clone(),
+    }
+    .spawn();
+    sleep(Duration::from_millis(10)).await;
+    shutdown.interrupt()?;
+    shutdown.event(Late)?;
+    timeout(Duration::from_secs(5), shutdown.join()).await??;
+    assert_eq!(*journal.lock().unwrap(), [(1, true), (1, true), (0, true)]);
+    Ok(())
+}
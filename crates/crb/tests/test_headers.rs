@@ -0,0 +1,200 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{Address, Agent, AgentSession, Headers, ManagedContext, OnEvent, Standalone};
+use crb::send::Sender;
+use crb::superagent::{AddressExt, OnRequest, OnResponse, Output, Request};
+
+#[derive(Default)]
+struct Server {
+    senders: Vec<String>,
+}
+
+impl Standalone for Server {}
+
+impl Agent for Server {
+    type Context = AgentSession<Self>;
+    type Output = Vec<String>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.senders)
+    }
+}
+
+struct Hello;
+
+#[async_trait]
+impl OnEvent<Hello> for Server {
+    async fn handle(&mut self, _: Hello, ctx: &mut Self::Context) -> Result<()> {
+        let headers = ctx.headers().ok_or_else(|| Error::msg("No headers"))?;
+        let sender = headers.sender.clone().unwrap_or_default();
+        self.senders.push(sender);
+        Ok(())
+    }
+}
+
+struct WhoAmI;
+
+impl Request for WhoAmI {
+    type Response = Option<String>;
+}
+
+#[async_trait]
+impl OnRequest<WhoAmI> for Server {
+    async fn on_request(&mut self, _: WhoAmI, ctx: &mut Self::Context) -> Result<Option<String>> {
+        let user = ctx
+            .headers()
+            .and_then(|headers| headers.get_tag("user"))
+            .map(String::from);
+        Ok(user)
+    }
+}
+
+#[tokio::test]
+async fn test_headers() -> Result<()> {
+    let mut addr = Server::default().spawn();
+
+    let headers = Headers::correlated().tag("user", "crab");
+    let user = addr.interact_with(WhoAmI, headers).await?;
+    assert_eq!(user.as_deref(), Some("crab"));
+    let user = addr.interact(WhoAmI).await?;
+    assert_eq!(user, None);
+
+    addr.event_with(Hello, Headers::new().sender("test"))?;
+    addr.interrupt()?;
+    let senders = addr.take_output().await?;
+    assert_eq!(senders, Some(vec!["test".to_string()]));
+    Ok(())
+}
+
+#[derive(Default)]
+struct Sink {
+    received: Vec<Headers>,
+}
+
+impl Standalone for Sink {}
+
+impl Agent for Sink {
+    type Context = AgentSession<Self>;
+    type Output = Vec<Headers>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.received)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Hello> for Sink {
+    async fn handle(&mut self, _: Hello, ctx: &mut Self::Context) -> Result<()> {
+        let headers = ctx.headers().cloned().unwrap_or_default();
+        self.received.push(headers);
+        Ok(())
+    }
+}
+
+/// Passes hellos to the sink in different ways.
+struct Proxy {
+    sink: Address<Sink>,
+}
+
+impl Standalone for Proxy {}
+
+impl Agent for Proxy {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+#[async_trait]
+impl OnEvent<Hello> for Proxy {
+    async fn handle(&mut self, _: Hello, _ctx: &mut Self::Context) -> Result<()> {
+        self.sink.event(Hello)?;
+        self.sink.recipient().send(Hello)?;
+        self.sink.send_async(Event::new(Hello)).await?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_forwarded_headers() -> Result<()> {
+    let mut sink = Sink::default().spawn();
+    let mut proxy = Proxy { sink: sink.clone() }.spawn();
+
+    let headers = Headers::correlated().tag("user", "crab").sender("test");
+    proxy.event_with(Hello, headers.clone())?;
+    proxy.interrupt()?;
+    proxy.join().await?;
+    // Messages sent outside of handlers have no headers
+    sink.event(Hello)?;
+    sink.interrupt()?;
+    let received = sink.take_output().await?.unwrap_or_default();
+
+    assert_eq!(received.len(), 4);
+    let proxy_id = proxy.id().to_string();
+    for forwarded in &received[..3] {
+        assert_eq!(forwarded.correlation_id, headers.correlation_id);
+        assert_eq!(forwarded.get_tag("user"), Some("crab"));
+        assert_eq!(forwarded.sender.as_ref(), Some(&proxy_id));
+        assert!(forwarded.timestamp.is_some());
+    }
+    assert_eq!(received[3].correlation_id, None);
+    Ok(())
+}
+
+/// Asks the server on behalf of a correlated message.
+struct Client {
+    server: Address<Server>,
+    responses: Vec<Option<Headers>>,
+}
+
+impl Standalone for Client {}
+
+impl Agent for Client {
+    type Context = AgentSession<Self>;
+    type Output = Vec<Option<Headers>>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.responses)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Hello> for Client {
+    async fn handle(&mut self, _: Hello, ctx: &mut Self::Context) -> Result<()> {
+        let address = ctx.address.clone();
+        self.server.interact(WhoAmI).forward_to(address, ());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OnResponse<Option<String>> for Client {
+    async fn on_response(
+        &mut self,
+        _: Output<Option<String>>,
+        _: (),
+        ctx: &mut Self::Context,
+    ) -> Result<()> {
+        self.responses.push(ctx.headers().cloned());
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_forwarded_response() -> Result<()> {
+    let server = Server::default().spawn();
+    let mut client = Client {
+        server,
+        responses: Vec::new(),
+    }
+    .spawn();
+    let headers = Headers::correlated().tag("user", "crab");
+    client.event_with(Hello, headers.clone())?;
+    let responses = client.take_output().await?.unwrap_or_default();
+
+    assert_eq!(responses.len(), 1);
+    let response = responses[0].as_ref().expect("No headers");
+    assert_eq!(response.correlation_id, headers.correlation_id);
+    assert_eq!(response.get_tag("user"), Some("crab"));
+    Ok(())
+}
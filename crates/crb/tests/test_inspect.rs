@@ -0,0 +1,86 @@
+use anyhow::Result;
+use crb::agent::{Agent, AgentSession, Next, Standalone, SupervisorSession};
+use crb::superagent::{ChildState, Supervisor, SupervisorAddress};
+use tokio::time::{sleep, timeout, Duration};
+
+struct Idle;
+
+impl Agent for Idle {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+struct Branch;
+
+impl Agent for Branch {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Idle, ());
+        ctx.spawn_agent(Idle, ());
+        Next::events()
+    }
+}
+
+impl Supervisor for Branch {
+    type GroupBy = ();
+
+    fn grace_period(_group: &()) -> Option<Duration> {
+        Some(Duration::from_millis(10))
+    }
+}
+
+struct Tree;
+
+impl Standalone for Tree {}
+
+impl Agent for Tree {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Idle, "leaves");
+        ctx.spawn_agent(Branch, "supervisors");
+        Next::events()
+    }
+}
+
+impl Supervisor for Tree {
+    type GroupBy = &'static str;
+
+    fn grace_period(_group: &&'static str) -> Option<Duration> {
+        Some(Duration::from_millis(100))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_inspect() -> Result<()> {
+    let mut tree = Tree.spawn();
+    sleep(Duration::from_millis(50)).await;
+
+    let snapshot = tree.snapshot().await?;
+    assert!(snapshot.supervisor.ends_with("Tree"));
+    assert_eq!(snapshot.children.len(), 2);
+    let leaf = &snapshot.children[0];
+    assert!(leaf.type_name.ends_with("Idle"));
+    assert_eq!(leaf.group, "\"leaves\"");
+    assert_eq!(leaf.state, ChildState::Running);
+    assert_eq!(leaf.uptime, Duration::from_millis(50));
+    assert!(leaf.children.is_empty());
+    let branch = &snapshot.children[1];
+    assert!(branch.type_name.ends_with("Branch"));
+    assert_eq!(branch.children.len(), 2);
+
+    let text = snapshot.to_string();
+    assert_eq!(text.lines().count(), 5);
+    assert!(text.lines().last().unwrap().starts_with("    #1 "));
+    let json = snapshot.to_json();
+    assert!(json.starts_with("{\"supervisor\":"));
+    assert!(json.contains("\"group\":\"\\\"supervisors\\\"\""));
+    assert_eq!(json.matches("\"state\":\"running\"").count(), 4);
+
+    tree.interrupt()?;
+    timeout(Duration::from_secs(5), tree.join()).await??;
+    Ok(())
+}
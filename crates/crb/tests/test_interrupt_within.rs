@@ -0,0 +1,49 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::address::AgentStatus;
+use crb::agent::{Agent, AgentSession, DoAsync, Next, Standalone};
+use crb::runtime::Interruptor;
+use tokio::time::{sleep, timeout, Duration};
+
+struct Stubborn;
+
+impl Standalone for Stubborn {}
+
+impl Agent for Stubborn {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Stubborn {
+    async fn perform(&mut self, _: (), _interruptor: Interruptor) -> Next<Self> {
+        sleep(Duration::from_secs(60)).await;
+        Next::done()
+    }
+}
+
+struct Polite;
+
+impl Standalone for Polite {}
+
+impl Agent for Polite {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+#[tokio::test]
+async fn test_interrupt_within() -> Result<()> {
+    let mut addr = Stubborn.spawn();
+    let deadline = Duration::from_millis(50);
+    timeout(Duration::from_secs(5), addr.interrupt_within(deadline)).await??;
+    let output = addr.join().await?;
+    assert!(matches!(output.status(), AgentStatus::Interrupted));
+
+    let mut addr = Polite.spawn();
+    timeout(Duration::from_secs(5), addr.interrupt_within(deadline)).await??;
+    Ok(())
+}
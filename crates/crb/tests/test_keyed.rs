@@ -0,0 +1,220 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Address, Agent, AgentContext, AgentSession, Context, ManagedContext, Next, OnEvent, Standalone,
+    SupervisorSession,
+};
+use crb::superagent::{Relation, Supervisor};
+use derive_more::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+struct Idle;
+
+impl Agent for Idle {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+/// Finishes right after the start.
+struct Guest;
+
+impl Agent for Guest {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.shutdown();
+        Next::events()
+    }
+}
+
+struct Hub {
+    journal: Journal<String>,
+}
+
+impl Standalone for Hub {}
+
+impl Agent for Hub {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_keyed("alice", Idle, "users").unwrap();
+        ctx.spawn_keyed("bob", Idle, "users").unwrap();
+        ctx.spawn_keyed("log", Idle, "services").unwrap();
+        if let Err(err) = ctx.spawn_keyed("bob", Idle, "users") {
+            self.journal.lock().unwrap().push(err.to_string());
+        }
+        ctx.spawn_keyed("guest", Guest, "guests").unwrap();
+        Next::events()
+    }
+}
+
+impl Supervisor for Hub {
+    type GroupBy = &'static str;
+
+    fn grace_period(_group: &&'static str) -> Option<Duration> {
+        Some(Duration::from_millis(10))
+    }
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Self::Context) {
+        if rel.group == "guests" {
+            let detached = ctx.child::<Guest>("guest").is_none();
+            self.journal
+                .lock()
+                .unwrap()
+                .push(format!("detached: {detached}"));
+        }
+    }
+}
+
+struct Check;
+
+#[async_trait]
+impl OnEvent<Check> for Hub {
+    async fn handle(&mut self, _event: Check, ctx: &mut Self::Context) -> Result<()> {
+        let mut journal = self.journal.lock().unwrap();
+        journal.push(format!("alice: {}", ctx.child::<Idle>("alice").is_some()));
+        journal.push(format!(
+            "wrong type: {}",
+            ctx.child::<Guest>("alice").is_some()
+        ));
+        let mut users: Vec<_> = ctx
+            .tracker
+            .children_in::<Idle>(&"users")
+            .map(|(key, _)| key.to_string())
+            .collect();
+        users.sort();
+        journal.push(format!("users: {}", users.join(", ")));
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keyed() -> Result<()> {
+    let journal = Journal::default();
+    let mut hub = Hub {
+        journal: journal.clone(),
+    }
+    .spawn();
+    hub.event(Check)?;
+    timeout(Duration::from_secs(5), hub.join()).await??;
+    // The guest may detach before or after the check
+    let mut journal = journal.lock().unwrap().clone();
+    journal.sort();
+    assert_eq!(
+        journal,
+        [
+            "The child with the key bob already exists",
+            "alice: true",
+            "detached: true",
+            "users: alice, bob",
+            "wrong type: false",
+        ]
+    );
+    Ok(())
+}
+
+/// Keeps a greeting for the clerk.
+#[derive(Deref, DerefMut)]
+struct ClerkContext {
+    #[deref]
+    #[deref_mut]
+    session: AgentSession<Clerk>,
+    greeting: &'static str,
+}
+
+impl Context for ClerkContext {
+    type Address = Address<Clerk>;
+
+    fn address(&self) -> &Self::Address {
+        self.session.address()
+    }
+}
+
+impl ManagedContext for ClerkContext {
+    fn is_alive(&self) -> bool {
+        self.session.is_alive()
+    }
+
+    fn shutdown(&mut self) {
+        self.session.shutdown();
+    }
+
+    fn stop(&mut self) {
+        self.session.stop();
+    }
+}
+
+impl AgentContext<Clerk> for ClerkContext {
+    fn session(&mut self) -> &mut AgentSession<Clerk> {
+        &mut self.session
+    }
+}
+
+/// Greets with the greeting of its context.
+struct Clerk {
+    journal: Journal<String>,
+}
+
+impl Agent for Clerk {
+    type Context = ClerkContext;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        self.journal.lock().unwrap().push(ctx.greeting.to_string());
+        Next::done()
+    }
+}
+
+struct Desk {
+    journal: Journal<String>,
+}
+
+impl Standalone for Desk {}
+
+impl Agent for Desk {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        let clerk = Clerk {
+            journal: self.journal.clone(),
+        };
+        let context = ClerkContext {
+            session: AgentSession::default(),
+            greeting: "Hi",
+        };
+        ctx.spawn_keyed_with_context("clerk", clerk, context, ())
+            .unwrap();
+        let keyed = ctx.child::<Clerk>("clerk").is_some();
+        self.journal.lock().unwrap().push(format!("keyed: {keyed}"));
+        Next::events()
+    }
+}
+
+impl Supervisor for Desk {
+    type GroupBy = ();
+
+    fn finished(&mut self, _rel: &Relation<Self>, ctx: &mut Self::Context) {
+        ctx.shutdown();
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keyed_with_context() -> Result<()> {
+    let journal = Journal::default();
+    let mut desk = Desk {
+        journal: journal.clone(),
+    }
+    .spawn();
+    timeout(Duration::from_secs(5), desk.join()).await??;
+    let mut journal = journal.lock().unwrap().clone();
+    journal.sort();
+    assert_eq!(journal, ["Hi", "keyed: true"]);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, MailboxStats, OnEvent, RunAgent, Task};
+use crb::runtime::InteractiveRuntime;
+use crb::superagent::{AddressExt, OnRequest, Request};
+use std::any::type_name;
+use std::collections::HashMap;
+
+struct Idle;
+
+impl Agent for Idle {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+struct Ping;
+
+#[async_trait]
+impl OnEvent<Ping> for Idle {
+    async fn handle(&mut self, _: Ping, _ctx: &mut Self::Context) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct Stats;
+
+impl Request for Stats {
+    type Response = MailboxStats;
+}
+
+#[async_trait]
+impl OnRequest<Stats> for Idle {
+    async fn on_request(&mut self, _: Stats, ctx: &mut Self::Context) -> Result<MailboxStats> {
+        Ok(ctx.mailbox_stats())
+    }
+}
+
+#[tokio::test]
+async fn test_mailbox_stats() -> Result<()> {
+    let runtime = RunAgent::new(Idle);
+    let mut addr = runtime.address();
+    for _ in 0..3 {
+        addr.event(Ping)?;
+    }
+    assert_eq!(addr.queue_len(), 3);
+    assert_eq!(addr.high_water_mark(), 3);
+
+    runtime.spawn();
+    let stats = addr.interact(Stats).await?;
+    assert_eq!(stats.queue_len, 0);
+    assert!(stats.high_water_mark >= 3);
+    // Counted by types of messages instead of their wrappers
+    let received = HashMap::from([(type_name::<Ping>(), 3), (type_name::<Stats>(), 1)]);
+    assert_eq!(stats.received, received);
+
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::{Error, Result};
+use crb::agent::{Agent, AgentSession, ManagedContext, Next, Standalone, SupervisorSession};
+use crb::superagent::{ChildOutcome, ChildStatus, Relation, Supervisor};
+
+#[derive(Default)]
+struct Collector {
+    outputs: Vec<u32>,
+    failures: Vec<String>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = SupervisorSession<Self>;
+    type Output = (Vec<u32>, Vec<String>);
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Answer { fail: false }, ());
+        ctx.spawn_agent(Answer { fail: true }, ());
+        Next::events()
+    }
+
+    fn end(self) -> Option<Self::Output> {
+        Some((self.outputs, self.failures))
+    }
+}
+
+impl Supervisor for Collector {
+    type GroupBy = ();
+
+    fn finished_with_outcome(
+        &mut self,
+        _rel: &Relation<Self>,
+        mut outcome: ChildOutcome,
+        ctx: &mut Self::Context,
+    ) {
+        match outcome.status {
+            ChildStatus::Done => {
+                assert!(outcome.take_output::<String>().is_none());
+                self.outputs.extend(outcome.take_output::<u32>());
+            }
+            ChildStatus::Failed(ref err) => self.failures.push(err.to_string()),
+            ChildStatus::Interrupted => {}
+        }
+        if ctx.tracker.is_empty() {
+            ctx.shutdown();
+        }
+    }
+}
+
+struct Answer {
+    fail: bool,
+}
+
+impl Agent for Answer {
+    type Context = AgentSession<Self>;
+    type Output = u32;
+
+    fn begin(&mut self) -> Next<Self> {
+        if self.fail {
+            Next::fail(Error::msg("Child has failed"))
+        } else {
+            Next::done()
+        }
+    }
+
+    fn end(self) -> Option<Self::Output> {
+        Some(42)
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_outcome() -> Result<()> {
+    let mut addr = Collector::default().spawn();
+    let output = addr.take_output().await?;
+    let expected = (vec![42], vec!["Child has failed".to_string()]);
+    assert_eq!(output, Some(expected));
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use crb::agent::{Agent, AgentSession, Next, Runnable, Standalone};
+
+/// A resource that can't be cloned.
+#[derive(Debug, PartialEq, Eq)]
+struct Handle(u8);
+
+struct Opener;
+
+impl Standalone for Opener {}
+
+impl Agent for Opener {
+    type Context = AgentSession<Self>;
+    type Output = Handle;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::output(Handle(1))
+    }
+}
+
+#[tokio::test]
+async fn test_output() -> Result<()> {
+    let mut addr = Opener.spawn();
+    let mut observer = addr.clone();
+    let handle = addr.take_output().await?;
+    assert_eq!(handle, Some(Handle(1)));
+
+    let mut output = observer.join().await?;
+    assert!(output.status().is_done());
+    assert_eq!(output.take(), None);
+
+    let handle = Opener.run().await?;
+    assert_eq!(handle, Some(Handle(1)));
+    Ok(())
+}
+
+struct Counter;
+
+impl Standalone for Counter {}
+
+impl Agent for Counter {
+    type Context = AgentSession<Self>;
+    type Output = u32;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::output(3)
+    }
+}
+
+#[tokio::test]
+async fn test_cloned_output() -> Result<()> {
+    let mut addr = Counter.spawn();
+    let mut observer = addr.clone();
+    assert_eq!(addr.join().await?.output(), Some(3));
+    assert_eq!(observer.join().await?.output(), Some(3));
+
+    let mut other = Counter.spawn();
+    let left = addr.join().await?;
+    let right = other.join().await?;
+    assert!(*left.status() == *right.status());
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use crb::agent::{Agent, AgentSession, Next, Standalone};
+use crb::agent::{
+    Address, Agent, AgentContext, AgentSession, Context, ManagedContext, Next, Standalone,
+};
 use crb_pipeline::Stage;
-use tokio::time::{sleep, Duration};
+use derive_more::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration};
 
 struct FirstProcessor {
     value: Option<u16>,
@@ -97,3 +101,104 @@ async fn test_pipeline() -> Result<(), Error> {
     addr.join().await?;
     Ok(())
 }
+
+#[derive(Deref, DerefMut)]
+struct ScaleContext {
+    #[deref]
+    #[deref_mut]
+    session: AgentSession<Scale>,
+    factor: u16,
+    results: Arc<Mutex<Vec<u16>>>,
+}
+
+impl Context for ScaleContext {
+    type Address = Address<Scale>;
+
+    fn address(&self) -> &Self::Address {
+        self.session.address()
+    }
+}
+
+impl ManagedContext for ScaleContext {
+    fn is_alive(&self) -> bool {
+        self.session.is_alive()
+    }
+
+    fn shutdown(&mut self) {
+        self.session.shutdown();
+    }
+
+    fn stop(&mut self) {
+        self.session.stop();
+    }
+}
+
+impl AgentContext<Scale> for ScaleContext {
+    fn session(&mut self) -> &mut AgentSession<Scale> {
+        &mut self.session
+    }
+}
+
+struct Scale {
+    value: u16,
+}
+
+impl Agent for Scale {
+    type Context = ScaleContext;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        let result = self.value * ctx.factor;
+        ctx.results.lock().unwrap().push(result);
+        Next::done()
+    }
+}
+
+#[async_trait]
+impl Stage for Scale {
+    type State = ();
+    type Config = ();
+    type Input = u8;
+    type Output = u16;
+
+    fn construct(_config: Self::Config, input: Self::Input, _state: &mut Self::State) -> Self {
+        Self {
+            value: input as u16,
+        }
+    }
+
+    async fn next_output(&mut self) -> Option<Self::Output> {
+        None
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_with_context() -> Result<(), Error> {
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let mut pipeline = Pipeline::new();
+
+    use crb_pipeline::*;
+    let shared = results.clone();
+    let stage = AgentStage::<Scale>::with_context((), move || ScaleContext {
+        session: AgentSession::default(),
+        factor: 3,
+        results: shared.clone(),
+    });
+    pipeline.stage(Input::<u8, _>::default(), stage);
+
+    let mut addr = pipeline.spawn();
+    addr.ingest(5u8)?;
+    addr.ingest(7u8)?;
+    timeout(Duration::from_secs(1), async {
+        while results.lock().unwrap().len() < 2 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    let mut values = results.lock().unwrap().clone();
+    values.sort();
+    assert_eq!(values, [15, 21]);
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Address, Agent, AgentContext, AgentSession, Context, ManagedContext, Next, OnEvent, Standalone,
+};
+use crb::superagent::{
+    AddressExt, Backoff, Intensity, OnRequest, Pool, PoolAddress, Request, Routing,
+};
+use derive_more::{Deref, DerefMut};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::time::{timeout, Duration};
+
+struct Worker {
+    id: usize,
+}
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+struct WhoAmI;
+
+impl Request for WhoAmI {
+    type Response = usize;
+}
+
+#[async_trait]
+impl OnRequest<WhoAmI> for Worker {
+    async fn on_request(&mut self, _: WhoAmI, _ctx: &mut Self::Context) -> Result<usize> {
+        Ok(self.id)
+    }
+}
+
+struct Crash;
+
+#[async_trait]
+impl OnEvent<Crash> for Worker {
+    async fn handle(&mut self, _: Crash, ctx: &mut Self::Context) -> Result<()> {
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+fn pool(size: usize) -> Pool<Worker> {
+    let counter = Arc::new(AtomicUsize::new(0));
+    Pool::new(size, move || Worker {
+        id: counter.fetch_add(1, Ordering::Relaxed),
+    })
+}
+
+#[tokio::test]
+async fn test_pool_round_robin() -> Result<()> {
+    let mut addr = pool(2).spawn();
+    let mut ids = HashSet::new();
+    for _ in 0..4 {
+        ids.insert(addr.interact(WhoAmI).await?);
+    }
+    assert_eq!(ids, HashSet::from([0, 1]));
+
+    addr.resize(3)?;
+    let mut ids = HashSet::new();
+    for _ in 0..3 {
+        ids.insert(addr.interact(WhoAmI).await?);
+    }
+    assert_eq!(ids, HashSet::from([0, 1, 2]));
+
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_restart() -> Result<()> {
+    let mut addr = pool(1).routing(Routing::ConsistentHash).spawn();
+    let id = addr.interact_keyed("user", WhoAmI).await?;
+    assert_eq!(addr.interact_keyed("user", WhoAmI).await?, id);
+
+    addr.event(Crash)?;
+    let mut restarted = id;
+    while restarted == id {
+        restarted = addr.interact(WhoAmI).await?;
+    }
+    assert_eq!(restarted, 1);
+
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
+
+struct Broken;
+
+impl Agent for Broken {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::fail(anyhow::Error::msg("Can't start"))
+    }
+}
+
+#[tokio::test]
+async fn test_pool_intensity() -> Result<()> {
+    let intensity = Intensity {
+        max_restarts: 3,
+        within: Duration::from_secs(60),
+    };
+    let mut addr = Pool::new(2, || Broken).intensity(intensity).spawn();
+    let output = timeout(Duration::from_secs(1), addr.join()).await??;
+    assert!(output.status().is_failed());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_backoff() -> Result<()> {
+    let backoff = Backoff {
+        initial: Duration::from_millis(50),
+        jitter: 0.0,
+        ..Backoff::default()
+    };
+    let mut addr = pool(2).backoff(backoff).spawn();
+    addr.event_keyed("user", Crash)?;
+    // Workers that wait for a restart are skipped
+    let restarted = async {
+        let mut ids = HashSet::new();
+        while !ids.contains(&2) {
+            // The crashed worker may still get a request
+            if let Ok(id) = addr.interact_keyed("user", WhoAmI).await {
+                ids.insert(id);
+            }
+        }
+        ids
+    };
+    let ids = timeout(Duration::from_secs(1), restarted).await?;
+    assert_eq!(ids.len(), 2);
+
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
+
+/// Keeps the id of a worker.
+#[derive(Deref, DerefMut)]
+struct MemberContext {
+    #[deref]
+    #[deref_mut]
+    session: AgentSession<Member>,
+    id: usize,
+}
+
+impl Context for MemberContext {
+    type Address = Address<Member>;
+
+    fn address(&self) -> &Self::Address {
+        self.session.address()
+    }
+}
+
+impl ManagedContext for MemberContext {
+    fn is_alive(&self) -> bool {
+        self.session.is_alive()
+    }
+
+    fn shutdown(&mut self) {
+        self.session.shutdown();
+    }
+
+    fn stop(&mut self) {
+        self.session.stop();
+    }
+}
+
+impl AgentContext<Member> for MemberContext {
+    fn session(&mut self) -> &mut AgentSession<Member> {
+        &mut self.session
+    }
+}
+
+struct Member;
+
+impl Agent for Member {
+    type Context = MemberContext;
+    type Output = ();
+}
+
+#[async_trait]
+impl OnRequest<WhoAmI> for Member {
+    async fn on_request(&mut self, _: WhoAmI, ctx: &mut Self::Context) -> Result<usize> {
+        Ok(ctx.id)
+    }
+}
+
+#[tokio::test]
+async fn test_pool_with_context() -> Result<()> {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut addr = Pool::with_context(2, move || {
+        let context = MemberContext {
+            session: AgentSession::default(),
+            id: counter.fetch_add(1, Ordering::Relaxed),
+        };
+        (Member, context)
+    })
+    .spawn();
+    let mut ids = HashSet::new();
+    for _ in 0..4 {
+        ids.insert(addr.interact(WhoAmI).await?);
+    }
+    assert_eq!(ids, HashSet::from([0, 1]));
+
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
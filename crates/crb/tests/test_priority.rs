@@ -0,0 +1,43 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{Agent, AgentSession, OnEvent, RunAgent, Task};
+use crb::runtime::InteractiveRuntime;
+
+#[derive(Default)]
+struct Journal {
+    records: Vec<&'static str>,
+}
+
+impl Agent for Journal {
+    type Context = AgentSession<Self>;
+    type Output = Vec<&'static str>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.records)
+    }
+}
+
+struct Record(&'static str);
+
+#[async_trait]
+impl OnEvent<Record> for Journal {
+    async fn handle(&mut self, event: Record, _ctx: &mut Self::Context) -> Result<()> {
+        self.records.push(event.0);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_priority() -> Result<()> {
+    let runtime = RunAgent::new(Journal::default());
+    let mut addr = runtime.address();
+    addr.event(Record("first"))?;
+    addr.event(Record("second"))?;
+    addr.send_priority(Event::new(Record("urgent")))?;
+    addr.interrupt()?;
+    runtime.spawn();
+    let output = addr.join().await?.output();
+    assert_eq!(output, Some(vec!["urgent", "first", "second"]));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Next, OnEvent, RunAgent, Standalone, Task};
+use crb::runtime::InteractiveRuntime;
+use crb::send::Sender;
+use crb::superagent::Registry;
+
+#[derive(Default)]
+struct Storage {
+    items: Vec<u32>,
+}
+
+impl Standalone for Storage {}
+
+impl Agent for Storage {
+    type Context = AgentSession<Self>;
+    type Output = Vec<u32>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.items)
+    }
+}
+
+struct Put(u32);
+
+#[async_trait]
+impl OnEvent<Put> for Storage {
+    async fn handle(&mut self, put: Put, _ctx: &mut Self::Context) -> Result<()> {
+        self.items.push(put.0);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_registry() -> Result<()> {
+    let mut addr = Storage::default().spawn();
+    Registry::register("storage", &addr)?.expose::<Put>();
+    Registry::register_singleton(&addr)?.expose::<Put>();
+    assert!(Registry::register("storage", &addr).is_err());
+
+    Registry::lookup::<Storage>("storage")
+        .unwrap()
+        .event(Put(1))?;
+    Registry::singleton::<Storage>().unwrap().event(Put(2))?;
+    Registry::recipient::<Put>("storage")
+        .unwrap()
+        .send(Put(3))?;
+    Registry::singleton_recipient::<Storage, Put>()
+        .unwrap()
+        .send(Put(4))?;
+    assert!(Registry::recipient::<u32>("storage").is_none());
+
+    addr.interrupt()?;
+    let items = addr.take_output().await?;
+    assert_eq!(items, Some(vec![1, 2, 3, 4]));
+
+    // Entries of finished agents are removed on lookups
+    assert!(Registry::singleton::<Storage>().is_none());
+    assert!(Registry::recipient::<Put>("storage").is_none());
+    assert!(Registry::lookup::<Storage>("storage").is_none());
+    Ok(())
+}
+
+struct Cache;
+
+impl Agent for Cache {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+#[test]
+fn test_registry_without_runtime() -> Result<()> {
+    let runtime = RunAgent::new(Cache);
+    let addr = runtime.address();
+    Registry::register_singleton(&addr)?;
+    assert!(Registry::singleton::<Cache>().is_some());
+    Registry::unregister_singleton::<Cache>();
+    assert!(Registry::singleton::<Cache>().is_none());
+    Registry::register_singleton(&addr)?;
+    Ok(())
+}
+
+struct Job;
+
+impl Agent for Job {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::done()
+    }
+}
+
+#[tokio::test]
+async fn test_finished_removed() -> Result<()> {
+    let runtime = RunAgent::new(Job);
+    let mut addr = runtime.address();
+    Registry::register("job-1", &addr)?;
+    assert!(Registry::names().contains(&"job-1".to_string()));
+    tokio::spawn(runtime.run());
+    addr.join().await?;
+    // Removed without a lookup
+    assert!(!Registry::names().contains(&"job-1".to_string()));
+    Ok(())
+}
@@ -0,0 +1,393 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb::agent::{
+    Address, Agent, AgentContext, AgentSession, Context, DoAsync, ManagedContext, Next, OnEvent,
+    Standalone, SupervisorSession,
+};
+use crb::superagent::{ChildSpec, Intensity, Restart, Strategy, Supervisor, SupervisorContext};
+use derive_more::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+type Records = Journal<(&'static str, &'static str, Instant)>;
+
+/// Waits until the condition is met. The time is paused in tests,
+/// so the waiting takes no time if the condition is never met.
+async fn wait_until(condition: impl Fn() -> bool) -> Result<()> {
+    let waiting = async {
+        while !condition() {
+            sleep(Duration::from_millis(1)).await;
+        }
+    };
+    timeout(Duration::from_secs(60), waiting).await?;
+    Ok(())
+}
+
+fn count(records: &Records, name: &str, event: &str) -> usize {
+    let records = records.lock().unwrap();
+    records
+        .iter()
+        .filter(|(n, e, _)| *n == name && *e == event)
+        .count()
+}
+
+/// Works for a while and fails during the first `failures` starts.
+/// Keeps running afterwards until it's interrupted.
+struct Worker {
+    name: &'static str,
+    works: Duration,
+    failures: usize,
+    records: Records,
+    failing: bool,
+}
+
+impl Worker {
+    fn spec<S>(self, group: S::GroupBy) -> ChildSpec<S>
+    where
+        S: Supervisor,
+        S::Context: SupervisorContext<S>,
+    {
+        let Self {
+            name,
+            works,
+            failures,
+            records,
+            ..
+        } = self;
+        ChildSpec::new(group, move || Worker {
+            name,
+            works,
+            failures,
+            records: records.clone(),
+            failing: false,
+        })
+    }
+
+    fn record(&self, event: &'static str) -> usize {
+        self.records
+            .lock()
+            .unwrap()
+            .push((self.name, event, Instant::now()));
+        count(&self.records, self.name, event)
+    }
+}
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        self.failing = self.record("start") <= self.failures;
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Worker {
+    async fn repeat(&mut self, _: &mut ()) -> Result<Option<Next<Self>>> {
+        if self.failing {
+            sleep(self.works).await;
+            self.record("fail");
+            Ok(Some(Next::fail(Error::msg("Broken"))))
+        } else {
+            sleep(Duration::from_millis(10)).await;
+            Ok(None)
+        }
+    }
+}
+
+impl Standalone for Worker {}
+
+/// Starts children by specs.
+struct Team {
+    children: Vec<ChildSpec<Self>>,
+    strategy: Strategy,
+    intensity: Intensity,
+}
+
+impl Team {
+    fn new(strategy: Strategy, max_restarts: usize, within: Duration) -> Self {
+        Self {
+            children: Vec::new(),
+            strategy,
+            intensity: Intensity {
+                max_restarts,
+                within,
+            },
+        }
+    }
+
+    fn child(mut self, spec: ChildSpec<Self>) -> Self {
+        self.children.push(spec);
+        self
+    }
+}
+
+impl Standalone for Team {}
+
+impl Agent for Team {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        for spec in self.children.drain(..) {
+            ctx.spawn_child(spec);
+        }
+        Next::events()
+    }
+}
+
+impl Supervisor for Team {
+    type GroupBy = ();
+
+    fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    fn intensity(&self) -> Intensity {
+        self.intensity
+    }
+}
+
+fn worker(name: &'static str, works: u64, failures: usize, records: &Records) -> Worker {
+    Worker {
+        name,
+        works: Duration::from_millis(works),
+        failures,
+        records: records.clone(),
+        failing: false,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_one_for_all() -> Result<()> {
+    let records = Records::default();
+    let third = worker("third", 0, 0, &records).spec(());
+    let mut team = Team::new(Strategy::OneForAll, 2, Duration::from_secs(60))
+        .child(worker("first", 0, 0, &records).spec(()))
+        .child(worker("second", 0, 1, &records).spec(()))
+        .child(third.restart(Restart::Temporary))
+        .spawn();
+    wait_until(|| count(&records, "second", "start") == 2).await?;
+    team.interrupt()?;
+    timeout(Duration::from_secs(5), team.join()).await??;
+    // The temporary child is not restarted
+    assert_eq!(count(&records, "first", "start"), 2);
+    assert_eq!(count(&records, "third", "start"), 1);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rest_for_one() -> Result<()> {
+    let records = Records::default();
+    let mut team = Team::new(Strategy::RestForOne, 2, Duration::from_secs(60))
+        .child(worker("first", 0, 0, &records).spec(()))
+        .child(worker("second", 10, 1, &records).spec(()))
+        .child(worker("third", 0, 0, &records).spec(()))
+        .spawn();
+    wait_until(|| count(&records, "third", "start") == 2).await?;
+    team.interrupt()?;
+    timeout(Duration::from_secs(5), team.join()).await??;
+    assert_eq!(count(&records, "first", "start"), 1);
+    assert_eq!(count(&records, "second", "start"), 2);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_restart_intensity() -> Result<()> {
+    let records = Records::default();
+    let mut team = Team::new(Strategy::OneForOne, 2, Duration::from_secs(60))
+        .child(worker("worker", 0, usize::MAX, &records).spec(()))
+        .spawn();
+    let output = timeout(Duration::from_secs(5), team.join()).await??;
+    assert!(output.status().is_failed());
+    // The first start and two restarts
+    assert_eq!(count(&records, "worker", "start"), 3);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_intensity_window_rollover() -> Result<()> {
+    // Restarts every 60ms leave one restart in a window of 100ms
+    let records = Records::default();
+    let mut team = Team::new(Strategy::OneForOne, 2, Duration::from_millis(100))
+        .child(worker("slow", 60, usize::MAX, &records).spec(()))
+        .spawn();
+    wait_until(|| count(&records, "slow", "start") == 10).await?;
+    team.interrupt()?;
+    let output = timeout(Duration::from_secs(5), team.join()).await??;
+    assert!(!output.status().is_failed());
+
+    // Restarts every 30ms exceed the limit
+    let records = Records::default();
+    let mut team = Team::new(Strategy::OneForOne, 2, Duration::from_millis(100))
+        .child(worker("fast", 30, usize::MAX, &records).spec(()))
+        .spawn();
+    let output = timeout(Duration::from_secs(5), team.join()).await??;
+    assert!(output.status().is_failed());
+    assert_eq!(count(&records, "fast", "start"), 3);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_failures() -> Result<()> {
+    // Children that fail at once are restarted together as one restart
+    let records = Records::default();
+    let mut team = Team::new(Strategy::OneForAll, 1, Duration::from_secs(60))
+        .child(worker("first", 10, 1, &records).spec(()))
+        .child(worker("second", 10, 1, &records).spec(()))
+        .child(worker("third", 0, 0, &records).spec(()))
+        .spawn();
+    wait_until(|| count(&records, "third", "start") == 2).await?;
+    sleep(Duration::from_millis(100)).await;
+    team.interrupt()?;
+    let output = timeout(Duration::from_secs(5), team.join()).await??;
+    assert!(!output.status().is_failed());
+    assert_eq!(count(&records, "first", "fail"), 1);
+    assert_eq!(count(&records, "second", "fail"), 1);
+    for name in ["first", "second", "third"] {
+        assert_eq!(count(&records, name, "start"), 2);
+    }
+    Ok(())
+}
+
+/// Comes in while the supervisor is terminating.
+struct Late;
+
+/// Respawns children by specs while it's terminating.
+struct Respawner {
+    records: Records,
+}
+
+impl Standalone for Respawner {}
+
+impl Agent for Respawner {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_child(worker("worker", 0, 0, &self.records).spec(()));
+        Next::events()
+    }
+}
+
+impl Supervisor for Respawner {
+    type GroupBy = ();
+}
+
+#[async_trait]
+impl OnEvent<Late> for Respawner {
+    async fn handle(&mut self, _: Late, ctx: &mut Self::Context) -> Result<()> {
+        ctx.spawn_child(worker("late", 0, 0, &self.records).spec(()));
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_specs_added_during_shutdown() -> Result<()> {
+    let records = Records::default();
+    let mut respawner = Respawner {
+        records: records.clone(),
+    }
+    .spawn();
+    respawner.interrupt()?;
+    respawner.event(Late)?;
+    timeout(Duration::from_secs(5), respawner.join()).await??;
+    // Late children are stopped and not restarted
+    assert_eq!(count(&records, "worker", "start"), 1);
+    assert_eq!(count(&records, "late", "start"), 1);
+    Ok(())
+}
+
+/// Knows the attempt it was created for.
+#[derive(Deref, DerefMut)]
+struct AttemptContext {
+    #[deref]
+    #[deref_mut]
+    session: AgentSession<Attempt>,
+    attempt: usize,
+}
+
+impl AttemptContext {
+    fn new(attempt: usize) -> Self {
+        Self {
+            session: AgentSession::default(),
+            attempt,
+        }
+    }
+}
+
+impl Context for AttemptContext {
+    type Address = Address<Attempt>;
+
+    fn address(&self) -> &Self::Address {
+        self.session.address()
+    }
+}
+
+impl ManagedContext for AttemptContext {
+    fn is_alive(&self) -> bool {
+        self.session.is_alive()
+    }
+
+    fn shutdown(&mut self) {
+        self.session.shutdown();
+    }
+
+    fn stop(&mut self) {
+        self.session.stop();
+    }
+}
+
+impl AgentContext<Attempt> for AttemptContext {
+    fn session(&mut self) -> &mut AgentSession<Attempt> {
+        &mut self.session
+    }
+}
+
+/// Fails during the first attempt.
+struct Attempt {
+    attempts: Journal<usize>,
+}
+
+impl Agent for Attempt {
+    type Context = AttemptContext;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        self.attempts.lock().unwrap().push(ctx.attempt);
+        if ctx.attempt == 0 {
+            Next::fail(Error::msg("Broken"))
+        } else {
+            Next::done()
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_spec_with_context() -> Result<()> {
+    let attempts = Journal::default();
+    let created = Arc::new(AtomicUsize::new(0));
+    let spec = {
+        let attempts = attempts.clone();
+        ChildSpec::with_context((), move || {
+            let agent = Attempt {
+                attempts: attempts.clone(),
+            };
+            let context = AttemptContext::new(created.fetch_add(1, Ordering::Relaxed));
+            (agent, context)
+        })
+    };
+    let mut team = Team::new(Strategy::OneForOne, 2, Duration::from_secs(60))
+        .child(spec.restart(Restart::Transient))
+        .spawn();
+    wait_until(|| attempts.lock().unwrap().len() == 2).await?;
+    team.interrupt()?;
+    timeout(Duration::from_secs(5), team.join()).await??;
+    // Every restart gets a new context
+    assert_eq!(*attempts.lock().unwrap(), [0, 1]);
+    Ok(())
+}
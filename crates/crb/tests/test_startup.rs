@@ -0,0 +1,151 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb::agent::{
+    Address, Agent, AgentSession, DoAsync, ManagedContext, Next, Standalone, SupervisorSession,
+};
+use crb::superagent::{Relation, Supervisor};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration};
+
+type Journal<T> = Arc<Mutex<Vec<T>>>;
+
+struct Loader {
+    journal: Journal<&'static str>,
+}
+
+impl Standalone for Loader {}
+
+impl Agent for Loader {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Loader {
+    async fn once(&mut self, _: &mut ()) -> Result<Next<Self>> {
+        sleep(Duration::from_millis(50)).await;
+        self.journal.lock().unwrap().push("loaded");
+        Ok(Next::events())
+    }
+}
+
+/// Finishes right after the start.
+struct Reader {
+    journal: Journal<&'static str>,
+}
+
+impl Agent for Reader {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        self.journal.lock().unwrap().push("reader");
+        ctx.shutdown();
+        Next::events()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Stage {
+    Config,
+    Readers,
+}
+
+struct Startup {
+    journal: Journal<&'static str>,
+    loader: Option<Address<Loader>>,
+    readers: usize,
+}
+
+impl Standalone for Startup {}
+
+impl Agent for Startup {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        let loader = Loader {
+            journal: self.journal.clone(),
+        };
+        let (loader, _) = ctx.spawn_agent(loader, Stage::Config);
+        self.loader = Some(loader);
+        // Readers are started when the loader is ready
+        for _ in 0..self.readers {
+            let reader = Reader {
+                journal: self.journal.clone(),
+            };
+            ctx.spawn_agent(reader, Stage::Readers);
+        }
+        Next::events()
+    }
+}
+
+impl Supervisor for Startup {
+    type GroupBy = Stage;
+
+    const ORDERED_STARTUP: bool = true;
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Self::Context) {
+        if rel.group == Stage::Readers {
+            self.readers -= 1;
+            if self.readers == 0 {
+                if let Some(loader) = self.loader.take() {
+                    loader.interrupt().ok();
+                }
+            }
+        }
+        if ctx.tracker.is_empty() {
+            ctx.shutdown();
+        }
+    }
+}
+
+/// Fails right after the start.
+struct Broken;
+
+impl Standalone for Broken {}
+
+impl Agent for Broken {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::fail(Error::msg("Broken"))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ordered_startup() -> Result<()> {
+    let journal = Journal::default();
+    let mut startup = Startup {
+        journal: journal.clone(),
+        loader: None,
+        readers: 2,
+    }
+    .spawn();
+    timeout(Duration::from_secs(5), startup.join()).await??;
+    assert_eq!(*journal.lock().unwrap(), ["loaded", "reader", "reader"]);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_address_ready() -> Result<()> {
+    let journal = Journal::default();
+    let mut loader = Loader {
+        journal: journal.clone(),
+    }
+    .spawn();
+    timeout(Duration::from_secs(5), loader.ready()).await??;
+    assert_eq!(*journal.lock().unwrap(), ["loaded"]);
+    loader.interrupt()?;
+    timeout(Duration::from_secs(5), loader.join()).await??;
+    assert!(loader.ready().await.is_ok());
+
+    // Agents that finish without being ready fail the waiting
+    assert!(Broken.spawn().ready().await.is_err());
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{Agent, AgentSession, OnEvent, Standalone};
+
+#[derive(Default)]
+struct Connection {
+    connected: bool,
+    handled: Vec<u8>,
+}
+
+impl Standalone for Connection {}
+
+impl Agent for Connection {
+    type Context = AgentSession<Self>;
+    type Output = Vec<u8>;
+
+    fn end(self) -> Option<Self::Output> {
+        Some(self.handled)
+    }
+}
+
+struct Handshake;
+
+#[async_trait]
+impl OnEvent<Handshake> for Connection {
+    async fn handle(&mut self, _: Handshake, ctx: &mut Self::Context) -> Result<()> {
+        self.connected = true;
+        ctx.unstash_all();
+        Ok(())
+    }
+}
+
+struct Query(u8);
+
+#[async_trait]
+impl OnEvent<Query> for Connection {
+    async fn handle(&mut self, query: Query, ctx: &mut Self::Context) -> Result<()> {
+        if self.connected {
+            self.handled.push(query.0);
+        } else {
+            ctx.stash_message(Event::new(query));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_stash() -> Result<()> {
+    let mut addr = Connection::default().spawn();
+    addr.event(Query(1))?;
+    addr.event(Query(2))?;
+    addr.event(Handshake)?;
+    addr.event(Query(3))?;
+    addr.interrupt()?;
+    let handled = addr.take_output().await?;
+    assert_eq!(handled, Some(vec![1, 2, 3]));
+    Ok(())
+}
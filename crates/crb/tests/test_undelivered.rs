@@ -0,0 +1,49 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{Agent, AgentSession, OnEvent, RunAgent, Task, Undelivered, UndeliveredReason};
+use crb::runtime::InteractiveRuntime;
+use crb::send::Sender;
+
+struct Worker;
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+struct Job(String);
+
+#[async_trait]
+impl OnEvent<Job> for Worker {
+    async fn handle(&mut self, _: Job, _ctx: &mut Self::Context) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_undelivered() -> Result<()> {
+    let runtime = RunAgent::new(Worker).bounded(1);
+    let mut addr = runtime.address();
+    addr.event(Job("first".into()))?;
+
+    let err = addr.event(Job("second".into())).unwrap_err();
+    let undelivered = err.downcast::<Undelivered<Job>>().unwrap();
+    assert_eq!(undelivered.reason(), UndeliveredReason::Full);
+    assert_eq!(undelivered.into_message().0, "second");
+
+    runtime.spawn();
+    addr.interrupt()?;
+    addr.join().await?;
+
+    let recipient = addr.recipient::<Job>();
+    let err = recipient.send(Job("third".into())).unwrap_err();
+    let undelivered = err.downcast::<Undelivered<Job>>().unwrap();
+    assert_eq!(undelivered.reason(), UndeliveredReason::Closed);
+    assert_eq!(undelivered.into_message().0, "third");
+
+    let err = addr.send(Event::new(Job("fourth".into()))).unwrap_err();
+    let undelivered = err.downcast::<Undelivered<Event<Job>>>().unwrap();
+    assert_eq!(undelivered.into_message().into_inner().0, "fourth");
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::message::event::Event;
+use crb::agent::{
+    Agent, AgentSession, DoAsync, ManagedContext, Next, OnEvent, RunAgent, Runnable,
+    SupervisorSession, Task,
+};
+use crb::runtime::InteractiveRuntime;
+use crb::send::Sender;
+use crb::superagent::{Relation, Supervisor};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration};
+
+struct Listener;
+
+impl Agent for Listener {
+    type Context = AgentSession<Self>;
+    type Output = ();
+}
+
+struct Ping;
+
+#[async_trait]
+impl OnEvent<Ping> for Listener {
+    async fn handle(&mut self, _: Ping, _ctx: &mut Self::Context) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_weak() -> Result<()> {
+    let runtime = RunAgent::new(Listener);
+    let weak = runtime.address().downgrade();
+    let sender = weak.sender();
+    sender.send(Event::new(Ping))?;
+    drop(runtime);
+    assert!(weak.upgrade().is_none());
+    assert!(sender.send(Event::new(Ping)).is_err());
+
+    let runtime = RunAgent::new(Listener);
+    let weak = runtime.address().downgrade();
+    runtime.spawn();
+    let mut addr = weak.upgrade().expect("The agent is alive");
+    addr.interrupt()?;
+    addr.join().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_last_strong_dropped() -> Result<()> {
+    let runtime = RunAgent::new(Listener);
+    let mut weak = runtime.address().downgrade();
+    runtime.spawn();
+    let addr = weak.upgrade().expect("The agent is alive");
+    addr.event(Ping)?;
+    drop(addr);
+    timeout(Duration::from_secs(1), weak.join()).await??;
+    assert!(weak.upgrade().is_none());
+    Ok(())
+}
+
+type Journal = Arc<Mutex<Vec<&'static str>>>;
+
+/// Finishes after a while.
+struct Slow;
+
+impl Agent for Slow {
+    type Context = AgentSession<Self>;
+    type Output = ();
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Slow {
+    async fn once(&mut self, _: &mut ()) -> Result<Next<Self>> {
+        sleep(Duration::from_millis(50)).await;
+        Ok(Next::done())
+    }
+}
+
+struct Parent {
+    journal: Journal,
+}
+
+impl Agent for Parent {
+    type Context = SupervisorSession<Self>;
+    type Output = ();
+
+    fn initialize(&mut self, ctx: &mut Self::Context) -> Next<Self> {
+        ctx.spawn_agent(Listener, "quick");
+        ctx.spawn_agent(Slow, "slow");
+        ctx.tracker.terminate_group("quick");
+        Next::events()
+    }
+
+    fn interrupt(&mut self, ctx: &mut Self::Context) {
+        self.journal.lock().unwrap().push("interrupted");
+        ctx.shutdown();
+    }
+}
+
+impl Supervisor for Parent {
+    type GroupBy = &'static str;
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Self::Context) {
+        self.journal.lock().unwrap().push(rel.group);
+        if ctx.tracker.is_empty() {
+            ctx.shutdown();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_run_in_place_not_released() -> Result<()> {
+    let journal = Journal::default();
+    let parent = Parent {
+        journal: journal.clone(),
+    };
+    // Detaching children don't release the supervisor that has no owners
+    timeout(Duration::from_secs(1), parent.run()).await??;
+    assert_eq!(*journal.lock().unwrap(), ["quick", "slow"]);
+    Ok(())
+}